@@ -0,0 +1,137 @@
+// Compara el recorrido lineal contra la BVH renderizando un campo de esferas y columnas sueltas
+// y un terreno grande de cubos, y mide el mismo terreno como mundo de bloques.
+// Ejecutar con `cargo bench --bench bvh`.
+use std::time::{Duration, Instant};
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::camera::Camera;
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::Cube;
+use computer_graphics_v3::framebuffer::Framebuffer;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::render::{render, RenderSettings};
+use computer_graphics_v3::scene::{Scene, ObjectProperties};
+use computer_graphics_v3::sphere::Sphere;
+use computer_graphics_v3::square::Square;
+use computer_graphics_v3::voxel::VoxelGrid;
+
+const WIDTH: usize = 320;
+const HEIGHT: usize = 240;
+const FRAMES: u32 = 5;
+
+fn time_frames(scene: &Scene, camera: &Camera, lights: &[Light]) -> Duration {
+    let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT);
+    // Un cuadro de calentamiento para cargar texturas perezosas
+    render(&mut framebuffer, scene, camera, lights, &RenderSettings::default());
+
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        render(&mut framebuffer, scene, camera, lights, &RenderSettings::default());
+    }
+    start.elapsed() / FRAMES
+}
+
+fn compare(name: &str, scene: &mut Scene, camera: &Camera, lights: &[Light]) {
+    scene.clear_bvh();
+    let linear = time_frames(scene, camera, lights);
+    scene.build_bvh();
+    let bvh = time_frames(scene, camera, lights);
+
+    println!(
+        "{:<10} {:>8} objetos  lineal {:>9.2?}  bvh {:>9.2?}  aceleración x{:.1}",
+        name,
+        scene.len(),
+        linear,
+        bvh,
+        linear.as_secs_f32() / bvh.as_secs_f32()
+    );
+}
+
+fn grass() -> Material {
+    Material::new(Color::from_srgb8(90, 160, 60), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0)
+}
+
+// Colinas suaves para que el terreno no sea un plano trivial
+fn terrain_height(x: i32, z: i32) -> i32 {
+    ((x as f32 * 0.3).sin() * (z as f32 * 0.2).cos() * 3.0).round() as i32 + 3
+}
+
+fn build_terrain(size: i32) -> Scene {
+    let grass = grass();
+    let mut scene = Scene::new();
+    for x in -size..size {
+        for z in -size..size {
+            for y in 0..terrain_height(x, z) {
+                let min = Vec3::new(x as f32, y as f32, z as f32);
+                scene.add(Cube::new(min, min + Vec3::new(1.0, 1.0, 1.0), grass.clone()), ObjectProperties::default());
+            }
+        }
+    }
+    scene
+}
+
+// Esferas de distintos tamaños con una columna al lado de cada una, sobre un suelo: muchas
+// primitivas independientes, que es lo que la BVH acelera
+fn build_scatter(size: i32) -> Scene {
+    let stone = Material::new(Color::from_srgb8(180, 180, 190), 30.0, [0.8, 0.2, 0.0, 0.0], 0.0);
+    let mut scene = Scene::new();
+    scene.add(
+        Square::new(Vec3::zeros(), Vec3::y(), size as f32 * 8.0, grass()),
+        ObjectProperties::default(),
+    );
+    for x in -size..size {
+        for z in -size..size {
+            let (cx, cz) = (x as f32 * 2.0, z as f32 * 2.0);
+            let radius = 0.3 + 0.2 * ((x * 7 + z * 13) as f32).sin().abs();
+            scene.add(Sphere::new(Vec3::new(cx, radius, cz), radius, stone.clone()), ObjectProperties::default());
+
+            let height = terrain_height(x, z) as f32 * 0.5;
+            let min = Vec3::new(cx + 0.6, 0.0, cz + 0.6);
+            scene.add(Cube::new(min, min + Vec3::new(0.4, height, 0.4), grass()), ObjectProperties::default());
+        }
+    }
+    scene
+}
+
+fn build_voxel_terrain(size: i32) -> (Scene, usize) {
+    let mut world = VoxelGrid::new(1.0);
+    let grass = world.register_block(grass(), ObjectProperties::default());
+    for x in -size..size {
+        for z in -size..size {
+            world.fill([x, 0, z], [x + 1, terrain_height(x, z), z + 1], grass);
+        }
+    }
+    let blocks = world.block_count();
+    let mut scene = Scene::new();
+    scene.add(world, ObjectProperties::default());
+    scene.build_bvh();
+    (scene, blocks)
+}
+
+fn main() {
+    let camera = Camera::new(
+        Vec3::new(30.0, 25.0, 30.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    let lights = vec![Light::new(Vec3::new(10.0, 40.0, 20.0), Color::white(), 1.0)];
+
+    let mut scatter = build_scatter(12);
+    compare("esferas", &mut scatter, &camera, &lights);
+
+    let mut terrain = build_terrain(16);
+    compare("terreno", &mut terrain, &camera, &lights);
+
+    let (voxels, blocks) = build_voxel_terrain(16);
+    println!("{:<10} {:>8} bloques  voxel  {:>9.2?}", "terreno", blocks, time_frames(&voxels, &camera, &lights));
+
+    // Un mundo de millones de bloques solo es viable con el recorrido DDA
+    let (voxels, blocks) = build_voxel_terrain(512);
+    let camera = Camera::new(
+        Vec3::new(80.0, 40.0, 80.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+    println!("{:<10} {:>8} bloques  voxel  {:>9.2?}", "mundo", blocks, time_frames(&voxels, &camera, &lights));
+}
@@ -0,0 +1,71 @@
+use rayon::prelude::*;
+
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+// Buffer de radiancia en punto flotante que suma muestras de varias pasadas.
+// Cada píxel guarda la suma ponderada por el filtro y el peso total, para
+// promediar correctamente aunque el filtro no sea de caja.
+pub struct Accumulator {
+    pub width: usize,
+    pub height: usize,
+    radiance: Vec<Color>,
+    weights: Vec<f32>,
+    frames: u32,
+}
+
+impl Accumulator {
+    pub fn new(width: usize, height: usize) -> Self {
+        Accumulator {
+            width,
+            height,
+            radiance: vec![Color::black(); width * height],
+            weights: vec![0.0; width * height],
+            frames: 0,
+        }
+    }
+
+    // Descarta lo acumulado, p. ej. cuando la cámara o la escena cambian
+    pub fn reset(&mut self) {
+        self.radiance.fill(Color::black());
+        self.weights.fill(0.0);
+        self.frames = 0;
+    }
+
+    // Pasadas acumuladas desde el último reinicio
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub(crate) fn par_pixels_mut(&mut self) -> impl IndexedParallelIterator<Item = (&mut Color, &mut f32)> {
+        self.radiance.par_iter_mut().zip(self.weights.par_iter_mut())
+    }
+
+    pub(crate) fn finish_frame(&mut self) {
+        self.frames += 1;
+    }
+
+    pub fn average(&self, x: usize, y: usize) -> Color {
+        let index = y * self.width + x;
+        let weight = self.weights[index];
+        if weight > 0.0 {
+            self.radiance[index] * (1.0 / weight)
+        } else {
+            Color::black()
+        }
+    }
+
+    // Escribe el promedio actual en el framebuffer con su curva de tonos
+    pub fn resolve(&self, framebuffer: &mut Framebuffer) {
+        assert!(framebuffer.width == self.width && framebuffer.height == self.height);
+        let tone_mapper = framebuffer.tone_mapper;
+        framebuffer
+            .buffer
+            .par_iter_mut()
+            .zip(self.radiance.par_iter().zip(self.weights.par_iter()))
+            .for_each(|(pixel, (radiance, weight))| {
+                let color = if *weight > 0.0 { *radiance * (1.0 / *weight) } else { Color::black() };
+                *pixel = tone_mapper.to_hex(color);
+            });
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::texture::{ColorSpace, Texture, TextureError};
+
+// Texturas que no se pudieron cargar, informadas todas juntas
+#[derive(Debug)]
+pub struct MissingAssets(pub Vec<TextureError>);
+
+impl fmt::Display for MissingAssets {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} recurso(s) no se pudieron cargar:", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MissingAssets {}
+
+// Carga de texturas de una escena. Cada archivo se lee una sola vez aunque lo usen
+// varias directivas, y los errores se acumulan en vez de cortar la carga: quien
+// llama decide al final si son fatales o si basta con los tableros de reemplazo.
+pub struct AssetManager {
+    placeholders: bool,
+    textures: HashMap<(PathBuf, ColorSpace), Arc<Texture>>,
+    errors: Vec<TextureError>,
+}
+
+impl AssetManager {
+    // Con `placeholders`, finish acepta las texturas faltantes como avisos
+    pub fn new(placeholders: bool) -> Self {
+        AssetManager {
+            placeholders,
+            textures: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    // Siempre devuelve una textura: si el archivo falla, registra el error y entrega
+    // un tablero de ajedrez para que la carga pueda seguir y encontrar el resto
+    pub fn texture<P: AsRef<Path>>(&mut self, path: P, color_space: ColorSpace) -> Arc<Texture> {
+        let key = (path.as_ref().to_path_buf(), color_space);
+        if let Some(texture) = self.textures.get(&key) {
+            return texture.clone();
+        }
+
+        let texture = match Texture::load_with_color_space(&key.0, color_space) {
+            Ok(texture) => texture,
+            Err(error) => {
+                self.errors.push(error);
+                Texture::checkerboard()
+            }
+        };
+        let texture = Arc::new(texture);
+        self.textures.insert(key, texture.clone());
+        texture
+    }
+
+    pub fn errors(&self) -> &[TextureError] {
+        &self.errors
+    }
+
+    // Termina la carga: devuelve los avisos, o todos los errores si no se aceptan reemplazos
+    pub fn finish(self) -> Result<Vec<TextureError>, MissingAssets> {
+        if self.errors.is_empty() || self.placeholders {
+            Ok(self.errors)
+        } else {
+            Err(MissingAssets(self.errors))
+        }
+    }
+}
+
+impl Default for AssetManager {
+    fn default() -> Self {
+        AssetManager::new(false)
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::texture::Texture;
+
+// Rectángulo de una textura en píxeles (y hacia abajo, como en la imagen)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Tile {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Tile { x, y, width, height }
+    }
+
+    // Toda la textura
+    pub fn full(texture: &Texture) -> Self {
+        Tile::new(0, 0, texture.width, texture.height)
+    }
+}
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AtlasError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            AtlasError::Parse { line, message } => write!(f, "línea {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+// Una sola imagen con varias texturas. Los mosaicos se piden por nombre (regiones
+// de un archivo de mosaicos) o, si el atlas tiene cuadrícula, como `columna,fila`.
+// Si la imagen no cargó (tablero de reemplazo), todo mosaico es la imagen completa.
+#[derive(Debug, Clone)]
+pub struct Atlas {
+    pub texture: Arc<Texture>,
+    grid: Option<(usize, usize)>,
+    tiles: HashMap<String, Tile>,
+}
+
+impl Atlas {
+    pub fn new(texture: Arc<Texture>) -> Self {
+        Atlas {
+            texture,
+            grid: None,
+            tiles: HashMap::new(),
+        }
+    }
+
+    // Divide la imagen en columnas x filas celdas iguales
+    pub fn set_grid(&mut self, columns: usize, rows: usize) -> Result<(), String> {
+        if columns == 0 || rows == 0 {
+            return Err("la cuadrícula necesita al menos una columna y una fila".to_string());
+        }
+        let divisible = self.texture.width.is_multiple_of(columns) && self.texture.height.is_multiple_of(rows);
+        if !divisible && !self.texture.is_placeholder() {
+            return Err(format!(
+                "la imagen de {}x{} no se divide en {}x{} celdas iguales",
+                self.texture.width, self.texture.height, columns, rows
+            ));
+        }
+        self.grid = Some((columns, rows));
+        Ok(())
+    }
+
+    pub fn grid_tile(&self, column: usize, row: usize) -> Option<Tile> {
+        let (columns, rows) = self.grid?;
+        if column >= columns || row >= rows {
+            return None;
+        }
+        if self.texture.is_placeholder() {
+            return Some(Tile::full(&self.texture));
+        }
+        let (width, height) = (self.texture.width / columns, self.texture.height / rows);
+        Some(Tile::new(column * width, row * height, width, height))
+    }
+
+    pub fn insert(&mut self, name: &str, tile: Tile) -> Result<(), String> {
+        let tile = if self.texture.is_placeholder() { Tile::full(&self.texture) } else { tile };
+        if tile.width == 0
+            || tile.height == 0
+            || tile.x + tile.width > self.texture.width
+            || tile.y + tile.height > self.texture.height
+        {
+            return Err(format!("el mosaico `{}` queda fuera de la imagen", name));
+        }
+        if self.tiles.insert(name.to_string(), tile).is_some() {
+            return Err(format!("mosaico `{}` repetido", name));
+        }
+        Ok(())
+    }
+
+    // Mosaico con nombre, o celda `columna,fila` de la cuadrícula
+    pub fn tile(&self, name: &str) -> Option<Tile> {
+        if let Some(tile) = self.tiles.get(name) {
+            return Some(*tile);
+        }
+        let (column, row) = name.split_once(',')?;
+        self.grid_tile(column.trim().parse().ok()?, row.trim().parse().ok()?)
+    }
+
+    pub fn load_tiles<P: AsRef<Path>>(&mut self, path: P) -> Result<(), AtlasError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|error| AtlasError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        self.parse_tiles(&source)
+    }
+
+    // Archivo de mosaicos: una entrada por línea, `#` inicia un comentario.
+    //
+    //   grid 16 16              (columnas y filas de celdas iguales)
+    //   dirt 2 0                (celda columna, fila de la cuadrícula)
+    //   glass 48 0 16 16        (rectángulo x, y, ancho, alto en píxeles)
+    pub fn parse_tiles(&mut self, source: &str) -> Result<(), AtlasError> {
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let error = |message: String| AtlasError::Parse { line, message };
+
+            let text = text.split('#').next().unwrap_or("").trim();
+            let tokens: Vec<&str> = text.split_whitespace().collect();
+            let Some((&name, args)) = tokens.split_first() else {
+                continue;
+            };
+            let mut numbers = Vec::with_capacity(args.len());
+            for arg in args {
+                numbers.push(arg.parse::<usize>().map_err(|_| error(format!("`{}` no es un entero válido", arg)))?);
+            }
+
+            match (name, numbers.as_slice()) {
+                ("grid", &[columns, rows]) => self.set_grid(columns, rows).map_err(error)?,
+                (_, &[column, row]) => {
+                    let tile = self
+                        .grid_tile(column, row)
+                        .ok_or_else(|| error(format!("la celda {},{} no existe en la cuadrícula", column, row)))?;
+                    self.insert(name, tile).map_err(error)?;
+                }
+                (_, &[x, y, width, height]) => self.insert(name, Tile::new(x, y, width, height)).map_err(error)?,
+                _ => return Err(error(format!("`{}` espera columna fila o x y ancho alto", name))),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+
+use crate::color::Color;
+
+// Rugosidad mínima: con luces puntuales un espejo perfecto daría un brillo infinitamente pequeño
+const MIN_ROUGHNESS: f32 = 0.03;
+
+// Reflectancia a incidencia normal de un dieléctrico con índice `ior`, visto desde el aire
+pub fn dielectric_f0(ior: f32) -> f32 {
+    ((ior - 1.0) / (ior + 1.0)).powi(2)
+}
+
+// Aproximación de Schlick a la reflectancia de Fresnel
+pub fn fresnel_schlick(f0: Color, cos_theta: f32) -> Color {
+    let weight = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 + (Color::white() - f0) * weight
+}
+
+// Fresnel exacto de un dieléctrico sin polarizar: fracción de la luz que se refleja cuando
+// pasa de un medio con índice `eta_i` a otro con `eta_t`. Vale 1 con reflexión interna total.
+pub fn fresnel_dielectric(cos_i: f32, eta_i: f32, eta_t: f32) -> f32 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin_t = eta_i / eta_t * (1.0 - cos_i * cos_i).max(0.0).sqrt();
+    if sin_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin_t * sin_t).sqrt();
+    let parallel = (eta_t * cos_i - eta_i * cos_t) / (eta_t * cos_i + eta_i * cos_t);
+    let perpendicular = (eta_i * cos_i - eta_t * cos_t) / (eta_i * cos_i + eta_t * cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.0
+}
+
+// Distribución de microfacetas GGX (Trowbridge-Reitz); `alpha` = rugosidad²
+pub fn ggx_distribution(n_dot_h: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+// Enmascaramiento de Smith para una dirección (forma de Schlick con k = alpha / 2)
+pub fn smith_g1(n_dot_x: f32, alpha: f32) -> f32 {
+    let k = alpha / 2.0;
+    n_dot_x / (n_dot_x * (1.0 - k) + k)
+}
+
+// Normal de microfaceta con probabilidad D(h) cos(h) alrededor de `normal`, a partir de dos
+// números uniformes en [0, 1)
+pub fn sample_ggx_normal(normal: &Vec3, alpha: f32, u1: f32, u2: f32) -> Vec3 {
+    let theta = (alpha * (u1 / (1.0 - u1)).sqrt()).atan();
+    let phi = 2.0 * PI * u2;
+    let (tangent, bitangent) = tangent_basis(normal);
+    (tangent * (theta.sin() * phi.cos()) + bitangent * (theta.sin() * phi.sin()) + normal * theta.cos()).normalize()
+}
+
+// Dirección del hemisferio de `normal` con probabilidad cos(θ) / π (reflexión de Lambert)
+pub fn sample_cosine_direction(normal: &Vec3, u1: f32, u2: f32) -> Vec3 {
+    let radius = u1.sqrt();
+    let phi = 2.0 * PI * u2;
+    let (tangent, bitangent) = tangent_basis(normal);
+    (tangent * (radius * phi.cos()) + bitangent * (radius * phi.sin()) + normal * (1.0 - u1).max(0.0).sqrt()).normalize()
+}
+
+// Base ortonormal cualquiera alrededor de la normal
+fn tangent_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let reference = if normal.x.abs() < 0.9 { Vec3::x() } else { Vec3::y() };
+    let tangent = reference.cross(normal).normalize();
+    (tangent, normal.cross(&tangent))
+}
+
+// Parámetros de una superficie metálica-rugosa en un punto
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceSample {
+    pub base_color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub ior: f32,
+}
+
+impl SurfaceSample {
+    // Reflectancia a incidencia normal: la de un dieléctrico, o el color base en los metales
+    pub fn f0(&self) -> Color {
+        let dielectric = Color::white() * dielectric_f0(self.ior);
+        dielectric * (1.0 - self.metallic) + self.base_color * self.metallic
+    }
+
+    // BRDF multiplicada por el coseno con la luz: lóbulo difuso de Lambert más el especular
+    // de Cook-Torrance con GGX. `view` y `light` salen del punto hacia la cámara y la luz.
+    pub fn evaluate(&self, normal: &Vec3, view: &Vec3, light: &Vec3) -> Color {
+        let n_dot_l = normal.dot(light);
+        let n_dot_v = normal.dot(view);
+        if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+            return Color::black();
+        }
+
+        let half = (view + light).normalize();
+        let n_dot_h = normal.dot(&half).max(0.0);
+        let v_dot_h = view.dot(&half).max(0.0);
+        let alpha = self.alpha();
+
+        let fresnel = fresnel_schlick(self.f0(), v_dot_h);
+        let d = ggx_distribution(n_dot_h, alpha);
+        let g = smith_g1(n_dot_l, alpha) * smith_g1(n_dot_v, alpha);
+        let specular = fresnel * (d * g / (4.0 * n_dot_l * n_dot_v));
+
+        // La luz que no se refleja en la superficie entra y se dispersa; los metales no tienen difusa
+        let kd = (Color::white() - fresnel) * (1.0 - self.metallic);
+        let diffuse = kd * self.base_color * (1.0 / PI);
+
+        (diffuse + specular) * n_dot_l
+    }
+
+    // Parámetro de GGX: el cuadrado de la rugosidad
+    pub fn alpha(&self) -> f32 {
+        self.roughness.max(MIN_ROUGHNESS).powi(2)
+    }
+
+    // Dirección de luz al azar para seguir un camino, eligiendo entre el lóbulo especular y el
+    // difuso según cuánto pesa cada uno. `u` son tres números uniformes en [0, 1); None si la
+    // dirección cae bajo la superficie.
+    pub fn sample(&self, normal: &Vec3, view: &Vec3, u: [f32; 3]) -> Option<Vec3> {
+        let light = if u[0] < self.specular_probability(normal.dot(view)) {
+            let half = sample_ggx_normal(normal, self.alpha(), u[1], u[2]);
+            half * (2.0 * view.dot(&half)) - view
+        } else {
+            sample_cosine_direction(normal, u[1], u[2])
+        };
+        (normal.dot(&light) > 0.0).then_some(light)
+    }
+
+    // Densidad por ángulo sólido con que `sample` elige `light`
+    pub fn pdf(&self, normal: &Vec3, view: &Vec3, light: &Vec3) -> f32 {
+        let n_dot_l = normal.dot(light);
+        if n_dot_l <= 0.0 {
+            return 0.0;
+        }
+        let half = (view + light).normalize();
+        let n_dot_h = normal.dot(&half).max(0.0);
+        let specular = ggx_distribution(n_dot_h, self.alpha()) * n_dot_h / (4.0 * view.dot(&half).max(1e-6));
+        let probability = self.specular_probability(normal.dot(view));
+        probability * specular + (1.0 - probability) * n_dot_l / PI
+    }
+
+    // Fracción de las muestras que van al lóbulo especular: la reflectancia de Fresnel frente a
+    // lo que refleja la difusa, sin dejar ningún lóbulo sin muestras
+    fn specular_probability(&self, n_dot_v: f32) -> f32 {
+        let specular = fresnel_schlick(self.f0(), n_dot_v).luminance();
+        let diffuse = self.base_color.luminance() * (1.0 - self.metallic) * (1.0 - specular);
+        (specular / (specular + diffuse).max(1e-6)).clamp(0.1, 0.9)
+    }
+}
@@ -0,0 +1,276 @@
+use nalgebra_glm::{Vec3, min2, max2};
+
+const SAH_BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+// Costos relativos usados por la heurística de área superficial (SAH)
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+
+// Caja alineada a los ejes
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(a: Vec3, b: Vec3) -> Self {
+        Aabb {
+            min: min2(&a, &b),
+            max: max2(&a, &b),
+        }
+    }
+
+    // Caja vacía: cualquier unión con ella devuelve la otra caja
+    pub fn empty() -> Self {
+        Aabb {
+            min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: min2(&self.min, &other.min),
+            max: max2(&self.max, &other.max),
+        }
+    }
+
+    pub fn grow(&mut self, point: &Vec3) {
+        self.min = min2(&self.min, point);
+        self.max = max2(&self.max, point);
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+        if d.x < 0.0 || d.y < 0.0 || d.z < 0.0 {
+            return 0.0;
+        }
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    // Eje con mayor extensión (0 = x, 1 = y, 2 = z)
+    pub fn largest_axis(&self) -> usize {
+        let d = self.max - self.min;
+        if d.x > d.y && d.x > d.z {
+            0
+        } else if d.y > d.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    // Prueba de slabs; devuelve la distancia de entrada si el rayo toca la caja antes de t_max
+    pub fn hit(&self, ray_origin: &Vec3, inv_dir: &Vec3, t_max: f32) -> Option<f32> {
+        let mut t_near = 0.0f32;
+        let mut t_far = t_max;
+
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - ray_origin[axis]) * inv_dir[axis];
+            let t1 = (self.max[axis] - ray_origin[axis]) * inv_dir[axis];
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+
+            // max/min ignoran NaN cuando el origen está justo sobre un plano paralelo
+            t_near = t_near.max(t0);
+            t_far = t_far.min(t1);
+            if t_near > t_far {
+                return None;
+            }
+        }
+
+        Some(t_near)
+    }
+}
+
+// Inverso de la dirección del rayo, precalculado una vez por consulta
+pub fn inverse_direction(ray_direction: &Vec3) -> Vec3 {
+    Vec3::new(
+        if ray_direction.x != 0.0 { 1.0 / ray_direction.x } else { f32::INFINITY },
+        if ray_direction.y != 0.0 { 1.0 / ray_direction.y } else { f32::INFINITY },
+        if ray_direction.z != 0.0 { 1.0 / ray_direction.z } else { f32::INFINITY },
+    )
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    bounds: Aabb,
+    // En hojas: índice del primer primitivo; en nodos internos: índice del hijo derecho
+    // (el hijo izquierdo siempre está inmediatamente después del padre)
+    offset: usize,
+    count: usize,
+}
+
+impl BvhNode {
+    fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+// Jerarquía de volúmenes envolventes construida con SAH sobre índices de primitivos
+#[derive(Debug, Clone, Default)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    bounds: Aabb,
+    count: usize,
+}
+
+impl Bvh {
+    pub fn build(bounds: &[Aabb]) -> Self {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(bounds.len() * 2),
+            indices: (0..bounds.len()).collect(),
+        };
+        if !bounds.is_empty() {
+            let centroids: Vec<Vec3> = bounds.iter().map(|b| b.centroid()).collect();
+            bvh.build_node(bounds, &centroids, 0, bounds.len());
+        }
+        bvh
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn build_node(&mut self, bounds: &[Aabb], centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let node_index = self.nodes.len();
+        let mut node_bounds = Aabb::empty();
+        let mut centroid_bounds = Aabb::empty();
+        for &i in &self.indices[start..end] {
+            node_bounds = node_bounds.union(&bounds[i]);
+            centroid_bounds.grow(&centroids[i]);
+        }
+        self.nodes.push(BvhNode {
+            bounds: node_bounds,
+            offset: start,
+            count: end - start,
+        });
+
+        let count = end - start;
+        if count <= MAX_LEAF_SIZE {
+            return node_index;
+        }
+
+        let axis = centroid_bounds.largest_axis();
+        let axis_min = centroid_bounds.min[axis];
+        let extent = centroid_bounds.max[axis] - axis_min;
+        if extent <= 0.0 {
+            // Todos los centroides coinciden: no hay partición útil
+            return node_index;
+        }
+
+        // Agrupa los primitivos en contenedores a lo largo del eje
+        let bin_of = |c: &Vec3| -> usize {
+            (((c[axis] - axis_min) / extent * SAH_BINS as f32) as usize).min(SAH_BINS - 1)
+        };
+        let mut bins = [Bin { bounds: Aabb::empty(), count: 0 }; SAH_BINS];
+        for &i in &self.indices[start..end] {
+            let b = bin_of(&centroids[i]);
+            bins[b].count += 1;
+            bins[b].bounds = bins[b].bounds.union(&bounds[i]);
+        }
+
+        // Evalúa el costo SAH de cada plano de corte entre contenedores
+        let mut best_cost = f32::INFINITY;
+        let mut best_split = 0;
+        for split in 1..SAH_BINS {
+            let (left, right) = bins.split_at(split);
+            let (left_bounds, left_count) = left.iter().fold((Aabb::empty(), 0), |(b, n), bin| (b.union(&bin.bounds), n + bin.count));
+            let (right_bounds, right_count) = right.iter().fold((Aabb::empty(), 0), |(b, n), bin| (b.union(&bin.bounds), n + bin.count));
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+            let cost = left_bounds.surface_area() * left_count as f32 + right_bounds.surface_area() * right_count as f32;
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        let parent_area = node_bounds.surface_area().max(f32::EPSILON);
+        let split_cost = TRAVERSAL_COST + INTERSECTION_COST * best_cost / parent_area;
+        let leaf_cost = INTERSECTION_COST * count as f32;
+        if best_split == 0 || split_cost >= leaf_cost {
+            return node_index;
+        }
+
+        // Particiona los índices in-place según el contenedor elegido
+        let mut mid = start;
+        for i in start..end {
+            if bin_of(&centroids[self.indices[i]]) < best_split {
+                self.indices.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        self.build_node(bounds, centroids, start, mid);
+        let right = self.build_node(bounds, centroids, mid, end);
+        let node = &mut self.nodes[node_index];
+        node.offset = right;
+        node.count = 0;
+        node_index
+    }
+
+    // Recorre la jerarquía de cerca a lejos llamando `visit` con cada primitivo candidato.
+    // `visit` devuelve la nueva distancia máxima de búsqueda (o None para detener el recorrido).
+    pub fn traverse<F>(&self, ray_origin: &Vec3, ray_direction: &Vec3, t_max: f32, mut visit: F)
+    where
+        F: FnMut(usize) -> Option<f32>,
+    {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let inv_dir = inverse_direction(ray_direction);
+        let mut t_max = t_max;
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0usize);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.bounds.hit(ray_origin, &inv_dir, t_max).is_none() {
+                continue;
+            }
+
+            if node.is_leaf() {
+                for &primitive in &self.indices[node.offset..node.offset + node.count] {
+                    match visit(primitive) {
+                        Some(t) => t_max = t_max.min(t),
+                        None => return,
+                    }
+                }
+                continue;
+            }
+
+            let left = node_index + 1;
+            let right = node.offset;
+            let t_left = self.nodes[left].bounds.hit(ray_origin, &inv_dir, t_max);
+            let t_right = self.nodes[right].bounds.hit(ray_origin, &inv_dir, t_max);
+
+            // Apila primero el hijo lejano para visitar antes el cercano
+            match (t_left, t_right) {
+                (Some(tl), Some(tr)) => {
+                    if tl <= tr {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+                (Some(_), None) => stack.push(left),
+                (None, Some(_)) => stack.push(right),
+                (None, None) => {}
+            }
+        }
+    }
+}
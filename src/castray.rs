@@ -1,12 +1,55 @@
-use crate::ray_intersect::{RayIntersect, Intersect};
+use std::f32::consts::PI;
+use crate::ray_intersect::Intersect;
+use crate::brdf::{fresnel_dielectric, fresnel_schlick, sample_ggx_normal, SurfaceSample};
 use crate::color::Color;
 use crate::light::Light;
-use crate::cube::Cube;
+use crate::material::Material;
+use crate::scene::Scene;
+use crate::sampling::Rng;
 use nalgebra_glm::{Vec3};
 
 const ORIGIN_BIAS: f32 = 1e-4;
-const SKYBOX_COLOR: Color = Color::new(253, 255, 146);
+// Rayos por lóbulo brillante en el primer impacto; los rebotes siguientes usan uno solo
+const GLOSSY_SAMPLES: u32 = 8;
+// Los rayos de sombra se detienen un poco antes de la luz, así las luces de área no quedan
+// tapadas por la superficie del bloque que las emite
+pub const LIGHT_SURFACE_BIAS: f32 = 1e-3;
+
+
+// Cono alrededor del rayo que cubre la huella de un píxel (ray cones). Su ancho en el
+// punto de impacto decide qué nivel de mipmap usar, así las texturas lejanas no parpadean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayCone {
+    pub width: f32,
+    // Ángulo de apertura en radianes
+    pub spread: f32,
+}
 
+impl RayCone {
+    // Cono que nace en la cámara con la apertura de un píxel
+    pub fn new(spread: f32) -> Self {
+        RayCone { width: 0.0, spread }
+    }
+
+    // Rayo sin huella: las texturas se muestrean a resolución completa
+    pub fn none() -> Self {
+        RayCone { width: 0.0, spread: 0.0 }
+    }
+
+    // El cono tras recorrer `distance`; los rebotes conservan la apertura (superficies planas)
+    pub fn at(self, distance: f32) -> Self {
+        RayCone {
+            width: self.width + self.spread * distance,
+            spread: self.spread,
+        }
+    }
+
+    // Ancho en UV de la huella sobre la superficie; crece cuando el rayo llega rasante
+    pub fn footprint(self, intersect: &Intersect, direction: &Vec3) -> f32 {
+        let cosine = intersect.normal.dot(&direction.normalize()).abs().max(1e-3);
+        self.width * intersect.uv_scale / cosine.sqrt()
+    }
+}
 
 pub fn offset_origin(intersect: &Intersect, direction: &Vec3) -> Vec3 {
     let offset = intersect.normal * ORIGIN_BIAS;
@@ -24,20 +67,20 @@ pub fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
 
 // maneja los rayos que entran y salen del ofecto dependiendo de el indica de refraccion del objecto 
 pub fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
-    let cosi = -incident.dot(normal).max(-1.0).min(1.0);
+    let cosi = -incident.dot(normal).clamp(-1.0, 1.0);
     
     let (n_cosi, eta, n_normal);
 
-    if cosi < 0.0 {
-        // Ray is entering the object
-        n_cosi = -cosi;
+    if cosi > 0.0 {
+        // Ray is entering the object (la normal apunta hacia el rayo)
+        n_cosi = cosi;
         eta = 1.0 / eta_t;
-        n_normal = -normal;
+        n_normal = *normal;
     } else {
         // Ray is leaving the object
-        n_cosi = cosi;
+        n_cosi = -cosi;
         eta = eta_t;  // Assuming it's going back into air with index 1.0
-        n_normal = *normal;
+        n_normal = -normal;
     }
     
     let k = 1.0 - eta * eta * (1.0 - n_cosi * n_cosi);
@@ -50,126 +93,249 @@ pub fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
     }
 }
 
-// determina si un punto de la escena tiene sombra
+// determina si un punto de la escena tiene sombra respecto al centro de la luz
 pub fn cast_shadow(
     intersect: &Intersect,
     light: &Light,
-    objects: &[Cube],
+    scene: &Scene,
 ) -> f32 {
-    let light_dir = (light.position - intersect.point).normalize();
-    let light_distance = (light.position - intersect.point).magnitude();
+    let (light_dir, light_distance) = light.direction_from(&intersect.point);
+    shadow_along(intersect, &light_dir, light_distance, scene)
+}
 
-    let shadow_ray_origin = offset_origin(intersect, &light_dir);
-    let mut shadow_intensity = 0.0;
+// Sombra a lo largo de una dirección hacia la luz; la distancia puede ser infinita (sol)
+pub fn shadow_along(intersect: &Intersect, light_dir: &Vec3, light_distance: f32, scene: &Scene) -> f32 {
+    let shadow_ray_origin = offset_origin(intersect, light_dir);
+    let mut transmitted = 1.0;
 
-    for object in objects {
+    scene.any_hit(&shadow_ray_origin, light_dir, light_distance, |object, shadow_intersect| {
         // Solo considera objetos que proyectan sombras
-        if object.has_shadow {
-            let shadow_intersect = object.ray_intersect(&shadow_ray_origin, &light_dir);
-
-            // Si hay una intersección y está dentro del rango de la luz
-            if shadow_intersect.is_intersecting && shadow_intersect.distance < light_distance {
-                let distance_ratio = shadow_intersect.distance / light_distance;
-
-                // Si el objeto es transparente, atenúa la sombra en función de la transparencia
-                if object.material.albedo[3] > 0.0 {
-                    // La luz pasa parcialmente según la transparencia del objeto
-                    shadow_intensity += (1.0 - object.material.albedo[3]) * (1.0 - distance_ratio.powf(2.0).min(1.0));
-                } else {
-                    // Si no es transparente, bloquea completamente la luz
-                    shadow_intensity = 1.0;
-                    break; // Salimos del bucle ya que la sombra es total
-                }
+        if !object.properties.casts_shadow {
+            return false;
+        }
+
+        let transparency = shadow_intersect.material.albedo[3];
+
+        // Cada superficie transparente deja pasar su fracción de la luz que le llega. La cara
+        // por la que el rayo sale (normal hacia la luz) es el mismo objeto y no se cuenta otra vez.
+        if object.properties.is_transparent || transparency > 0.0 {
+            if shadow_intersect.normal.dot(light_dir) <= 0.0 {
+                transmitted *= transparency;
             }
+            false
+        } else {
+            // Si no es transparente, bloquea completamente la luz
+            transmitted = 0.0;
+            true // Salimos de la búsqueda ya que la sombra es total
         }
-    }
+    });
+
+    1.0 - transmitted
+}
 
-    shadow_intensity.min(1.0) // Asegura que el valor de la sombra no sea mayor a 1.0
+// Sombra promedio de `light.samples` rayos hacia puntos al azar de la luz: con luces de área
+// la fracción tapada da la penumbra
+pub fn sample_shadow(intersect: &Intersect, light: &Light, scene: &Scene, rng: &mut Rng) -> f32 {
+    let mut shadow = 0.0;
+    for _ in 0..light.samples {
+        let (light_dir, light_distance) = light.sample_direction(&intersect.point, rng);
+        shadow += shadow_along(intersect, &light_dir, light_distance - LIGHT_SURFACE_BIAS, scene);
+    }
+    shadow / light.samples as f32
 }
 
+// Dirección hacia un punto al azar de la luz y la fracción de su intensidad que llega por ella:
+// la atenuación hacia ese punto y lo que tapa el camino. Con luces de área cada muestra cambia
+// el ángulo y la distancia, no solo la sombra.
+fn sample_light(intersect: &Intersect, light: &Light, scene: &Scene, rng: &mut Rng) -> (Vec3, f32) {
+    let (light_dir, light_distance) = light.sample_direction(&intersect.point, rng);
+    let attenuation = light.attenuation_along(&intersect.point, &light_dir, light_distance);
+    if attenuation <= 0.0 {
+        return (light_dir, 0.0);
+    }
+    let shadow = shadow_along(intersect, &light_dir, light_distance - LIGHT_SURFACE_BIAS, scene);
+    (light_dir, attenuation * (1.0 - shadow))
+}
 
 // solo se muestra si el rayo intersecta 
 pub fn cast_ray(
     ray_origin: &Vec3,
     ray_direction: &Vec3,
-    objects: &[Cube],
+    scene: &Scene,
     lights: &[Light],  // Ahora acepta un arreglo de luces
     depth: u32,        // Recursión de reflexión/refracción
+    cone: RayCone,     // Huella del píxel a lo largo del rayo
+    rng: &mut Rng,     // Muestras aleatorias de los lóbulos brillantes
 ) -> Color {
     if depth > 3 {
-        return SKYBOX_COLOR;
+        return scene.sky.color(ray_direction);
     }
 
     
 
-    let mut intersect = Intersect::empty();
-    let mut zbuffer = f32::INFINITY;
-
     // Buscar la intersección más cercana
-    for object in objects {
-        let i = object.ray_intersect(ray_origin, ray_direction);
-        if i.is_intersecting && i.distance < zbuffer {
-            zbuffer = i.distance;
-            intersect = i;
-        }
-    }
+    let intersect = scene.intersect(ray_origin, ray_direction);
 
  
 
     if !intersect.is_intersecting {
-        return SKYBOX_COLOR;  // Fondo de cielo por defecto si no hay intersección
+        return scene.sky.color(ray_direction);  // Fondo de cielo si no hay intersección
     }
 
-    let material = &intersect.material;
-    let mut pixel_color = Color::new(0, 0, 0);
-
-    if material.has_emission {
-        let emission_color = material.get_emission_color(intersect.u, intersect.v);
-        pixel_color += emission_color; // Sumar el color de emisión
+    let cone = cone.at(intersect.distance * ray_direction.magnitude());
+    let footprint = cone.footprint(&intersect, ray_direction);
+
+    // Luz propia de los materiales emisivos, que se suma a lo que reflejan
+    let emission = intersect.material.get_emission_color(intersect.u, intersect.v, footprint);
+
+    let diffuse_color = intersect.material.get_diffuse_color(intersect.u, intersect.v, footprint);
+    let surface = intersect.material.surface_at(intersect.u, intersect.v, footprint);
+    let cos_i = ray_direction.normalize().dot(&intersect.normal);
+
+    // Promedio de los rayos reflejados (o refractados con el índice `refraction`) repartidos
+    // sobre el lóbulo de la rugosidad; una superficie lisa usa un único rayo perfecto
+    let trace_lobe = |rng: &mut Rng, roughness: f32, refraction: Option<f32>| {
+        let samples = if roughness > 0.0 && depth == 0 { GLOSSY_SAMPLES } else { 1 };
+        let mut sum = Color::black();
+        for _ in 0..samples {
+            let microfacet = microfacet_normal(&intersect.normal, roughness, rng);
+            let direction = match refraction {
+                Some(refractive_index) => refract(ray_direction, &microfacet, refractive_index),
+                None => {
+                    let glossy = reflect(ray_direction, &microfacet);
+                    // Una microfaceta muy inclinada puede mandar el reflejo hacia adentro
+                    if glossy.dot(&intersect.normal) * cos_i < 0.0 {
+                        glossy
+                    } else {
+                        reflect(ray_direction, &intersect.normal)
+                    }
+                }
+            }
+            .normalize();
+            let origin = offset_origin(&intersect, &direction);
+            let mut color = cast_ray(&origin, &direction, scene, lights, depth + 1, cone, rng);
+
+            // Beer-Lambert: el volumen absorbe según lo que el rayo recorre hasta salir
+            if refraction.is_some() && cos_i < 0.0 && !intersect.material.absorption.is_black() {
+                if let Some(distance) = scene.exit_distance(&origin, &direction) {
+                    color = color * intersect.material.transmittance(distance);
+                }
+            }
+            sum += color;
+        }
+        sum * (1.0 / samples as f32)
+    };
+
+    if let Some(surface) = surface {
+        // Reflejo del entorno con el Fresnel del ángulo de vista
+        let mut color = shade_pbr(&intersect, &surface, ray_direction, scene, lights, rng);
+        let reflectance = fresnel_schlick(surface.f0(), -cos_i);
+        if reflectance.luminance() > 1e-3 {
+            color += trace_lobe(rng, surface.roughness, None) * reflectance;
+        }
+        return color + emission;
     }
 
-
     // Inicializar los colores de difusión y especular
     let mut final_color = Color::black();
 
     // Iterar sobre todas las luces para acumular contribuciones
+    let view_dir = (ray_origin - intersect.point).normalize();
     for light in lights {
-        let light_dir = (light.position - intersect.point).normalize();
-        let view_dir = (ray_origin - intersect.point).normalize();
-        let reflect_dir = reflect(&-light_dir, &intersect.normal).normalize();
+        let cookie = light.cookie_at(&intersect.point);
+        let mut light_color = Color::black();
+
+        for _ in 0..light.samples {
+            let (light_dir, arriving) = sample_light(&intersect, light, scene, rng);
+            let light_intensity = light.intensity * arriving;
+            let reflect_dir = reflect(&-light_dir, &intersect.normal).normalize();
 
-        let shadow_intensity = cast_shadow(&intersect, light, objects);
-        let light_intensity = light.intensity * (1.0 - shadow_intensity);
+            // Componente difusa
+            let diffuse_intensity = intersect.normal.dot(&light_dir).clamp(0.0, 1.0);
+            let diffuse = diffuse_color * cookie * intersect.material.albedo[0] * diffuse_intensity * light_intensity;
 
-        // Componente difusa
-        let diffuse_intensity = intersect.normal.dot(&light_dir).max(0.0).min(1.0);
-        let diffuse_color = intersect.material.get_diffuse_color(intersect.u, intersect.v);
-        let diffuse = diffuse_color * intersect.material.albedo[0] * diffuse_intensity * light_intensity;
+            // Componente especular
+            let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(intersect.material.specular);
+            let specular = light.color * cookie * intersect.material.albedo[1] * specular_intensity * light_intensity;
 
-        // Componente especular
-        let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(intersect.material.specular);
-        let specular = light.color * intersect.material.albedo[1] * specular_intensity * light_intensity;
+            light_color = light_color + diffuse + specular;
+        }
 
-        // Sumar la contribución de esta luz al color final
-        final_color = final_color + diffuse + specular;
+        // Sumar el promedio de las muestras de esta luz al color final
+        final_color += light_color * (1.0 / light.samples as f32);
     }
 
     // Manejo de reflejos y refracciones
-    let mut reflect_color = Color::black();
     let reflectivity = intersect.material.albedo[2];
-    if reflectivity > 0.0 {
-        let reflect_dir = reflect(&ray_direction, &intersect.normal).normalize();
-        let reflect_origin = offset_origin(&intersect, &reflect_dir);
-        reflect_color = cast_ray(&reflect_origin, &reflect_dir, objects, lights, depth + 1);
+    let transparency = intersect.material.albedo[3];
+    let refractive_index = intersect.material.refractive_index;
+    let roughness = intersect.material.roughness;
+    let (reflected, transmitted) = reflection_weights(&intersect.material, cos_i);
+
+    let mut reflect_color = Color::black();
+    if reflected > 0.0 {
+        reflect_color = trace_lobe(rng, roughness, None);
     }
 
     let mut refract_color = Color::black();
-    let transparency = intersect.material.albedo[3];
-    if transparency > 0.0 {
-        let refract_dir = refract(&ray_direction, &intersect.normal, intersect.material.refractive_index);
-        let refract_origin = offset_origin(&intersect, &refract_dir);
-        refract_color = cast_ray(&refract_origin, &refract_dir, objects, lights, depth + 1);
+    if transmitted > 0.0 {
+        refract_color = trace_lobe(rng, roughness, Some(refractive_index));
+    }
+
+    final_color * (1.0 - reflectivity - transparency) + reflect_color * reflected + refract_color * transmitted + emission
+}
+
+// Pesos del reflejo y de la refracción del modelo clásico para un rayo que llega con `cos_i`
+// (respecto a la normal exterior). La parte transparente de un dieléctrico se reparte entre
+// ambos según el ángulo: casi todo se refracta de frente y casi todo se refleja de canto.
+pub fn reflection_weights(material: &Material, cos_i: f32) -> (f32, f32) {
+    let reflectivity = material.albedo[2];
+    let transparency = material.albedo[3];
+    let refractive_index = material.refractive_index;
+    let fresnel = if transparency > 0.0 && refractive_index > 0.0 {
+        if cos_i < 0.0 {
+            fresnel_dielectric(-cos_i, 1.0, refractive_index)
+        } else {
+            fresnel_dielectric(cos_i, refractive_index, 1.0)
+        }
+    } else {
+        0.0
+    };
+    (reflectivity + transparency * fresnel, transparency * (1.0 - fresnel))
+}
+
+// Normal de una microfaceta al azar según GGX; la superficie lisa no se perturba
+pub fn microfacet_normal(normal: &Vec3, roughness: f32, rng: &mut Rng) -> Vec3 {
+    if roughness <= 0.0 {
+        return *normal;
+    }
+    sample_ggx_normal(normal, roughness * roughness, rng.next_f32(), rng.next_f32())
+}
+
+// Luz directa de los materiales metálico-rugosos: la BRDF GGX evaluada en cada luz
+fn shade_pbr(
+    intersect: &Intersect,
+    surface: &SurfaceSample,
+    ray_direction: &Vec3,
+    scene: &Scene,
+    lights: &[Light],
+    rng: &mut Rng,
+) -> Color {
+    let normal = intersect.normal;
+    let view_dir = -ray_direction.normalize();
+    let mut color = Color::black();
+
+    for light in lights {
+        // Escala de modo que una superficie blanca mate de frente a la luz valga `intensity`,
+        // igual que en el modelo clásico
+        let light_color = light.color * light.cookie_at(&intersect.point) * (light.intensity * PI / light.samples as f32);
+        for _ in 0..light.samples {
+            let (light_dir, arriving) = sample_light(intersect, light, scene, rng);
+            if arriving > 0.0 {
+                color += surface.evaluate(&normal, &view_dir, &light_dir) * light_color * arriving;
+            }
+        }
     }
 
-    final_color * (1.0 - reflectivity - transparency) + reflect_color * reflectivity + refract_color * transparency
+    color
 }
@@ -1,40 +1,113 @@
 use std::fmt;
 use std::ops::AddAssign;
+use once_cell::sync::Lazy;
 
+// Tabla de decodificación sRGB -> lineal para valores de 8 bits
+static SRGB_TO_LINEAR: Lazy<[f32; 256]> = Lazy::new(|| {
+    let mut table = [0.0; 256];
+    for (i, value) in table.iter_mut().enumerate() {
+        *value = srgb_to_linear(i as f32 / 255.0);
+    }
+    table
+});
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
 
-#[derive(Debug, Clone, Copy)]
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0.0, 1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+
+// Radiancia en espacio lineal con componentes f32 sin límite superior.
+// 1.0 equivale al antiguo 255; la conversión a 8 bits ocurre solo al escribir en el framebuffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
 }
 
 impl Color {
-    // Constructor to initialize the color using r, g, b values
-    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+    // Constructor to initialize the color using linear r, g, b values
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
         Color { r, g, b }
     }
 
+    // Constructor from 8-bit components (0-255 maps to 0.0-1.0)
+    pub const fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+        Color {
+            r: r as f32 / 255.0,
+            g: g as f32 / 255.0,
+            b: b as f32 / 255.0,
+        }
+    }
+
+    // Constructor from 8-bit sRGB components, decoded to linear radiance
+    pub fn from_srgb8(r: u8, g: u8, b: u8) -> Self {
+        let table = &*SRGB_TO_LINEAR;
+        Color {
+            r: table[r as usize],
+            g: table[g as usize],
+            b: table[b as usize],
+        }
+    }
+
     // Function to create a color from a hex value
     pub const fn from_hex(hex: u32) -> Self {
         let r = ((hex >> 16) & 0xFF) as u8;
         let g = ((hex >> 8) & 0xFF) as u8;
         let b = (hex & 0xFF) as u8;
-        Color { r, g, b }
+        Color::from_rgb8(r, g, b)
     }
 
     pub const fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color { r: 0.0, g: 0.0, b: 0.0 }
+    }
+
+    pub const fn white() -> Self {
+        Color { r: 1.0, g: 1.0, b: 1.0 }
+    }
+
+    // Componentes recortados a 0-255
+    pub fn to_rgb8(self) -> [u8; 3] {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        [channel(self.r), channel(self.g), channel(self.b)]
     }
 
     // Function to return the color as a hex value
-    pub fn to_hex(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    pub fn to_hex(self) -> u32 {
+        let [r, g, b] = self.to_rgb8();
+        ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
     }
 
     // Function to return the color as a hex value
-    pub fn is_black(&self) -> bool {
-        self.r == 0 && self.g == 0 && self.b == 0
+    pub fn is_black(self) -> bool {
+        self.r == 0.0 && self.g == 0.0 && self.b == 0.0
+    }
+
+    // Codifica cada componente con la curva sRGB (después de recortar a 0-1)
+    pub fn to_srgb(self) -> Color {
+        Color {
+            r: linear_to_srgb(self.r),
+            g: linear_to_srgb(self.g),
+            b: linear_to_srgb(self.b),
+        }
+    }
+
+    // Luminancia relativa (Rec. 709)
+    pub fn luminance(self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
     }
 }
 
@@ -46,9 +119,24 @@ impl Add for Color {
 
     fn add(self, other: Color) -> Color {
         Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+        }
+    }
+}
+
+// Resta componente a componente (p. ej. la energía que no refleja el Fresnel)
+use std::ops::Sub;
+
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, other: Color) -> Color {
+        Color {
+            r: self.r - other.r,
+            g: self.g - other.g,
+            b: self.b - other.b,
         }
     }
 }
@@ -61,9 +149,22 @@ impl Mul<f32> for Color {
 
     fn mul(self, scalar: f32) -> Color {
         Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+            r: self.r * scalar,
+            g: self.g * scalar,
+            b: self.b * scalar,
+        }
+    }
+}
+
+// Componente a componente, para filtrar luz por el color de una superficie
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        Color {
+            r: self.r * other.r,
+            g: self.g * other.g,
+            b: self.b * other.b,
         }
     }
 }
@@ -81,4 +182,4 @@ impl AddAssign for Color {
         self.g += other.g;
         self.b += other.b;
     }
-}
\ No newline at end of file
+}
@@ -1,48 +1,117 @@
 use crate::material::Material;
 use nalgebra_glm::{Vec3, min2, max2};
-use crate::ray_intersect::{RayIntersect, Intersect};
+use crate::ray_intersect::{RayIntersect, Intersect, Face};
+use crate::bvh::Aabb;
+
+// Un material por cara, para bloques con arte distinto arriba, abajo y a los lados
+#[derive(Debug, Clone)]
+pub struct FaceMaterials {
+    materials: [Material; 6],
+}
+
+impl FaceMaterials {
+    // El mismo material en las seis caras
+    pub fn uniform(material: Material) -> Self {
+        FaceMaterials {
+            materials: std::array::from_fn(|_| material.clone()),
+        }
+    }
+
+    // Distribución típica de un bloque: tapa, base y las cuatro caras laterales
+    pub fn top_bottom_sides(top: Material, bottom: Material, sides: Material) -> Self {
+        let mut materials = FaceMaterials::uniform(sides);
+        materials.set(Face::Top, top);
+        materials.set(Face::Bottom, bottom);
+        materials
+    }
+
+    pub fn get(&self, face: Face) -> &Material {
+        &self.materials[face.index()]
+    }
+
+    pub fn set(&mut self, face: Face, material: Material) {
+        self.materials[face.index()] = material;
+    }
+}
 
 pub struct Cube {
     pub min: Vec3, // Una esquina del cubo
     pub max: Vec3, // La esquina opuesta del cubo
-    pub material: Material,
-    pub has_shadow: bool, 
-    pub is_transparent: bool, 
+    pub materials: FaceMaterials,
 }
 
 impl Cube {
-    // Obtén las coordenadas UV para el cubo
-    fn get_uv(&self, point: &Vec3) -> (f32, f32) {
-        let mut u = 0.0;
-        let mut v = 0.0;
-
-        // Detecta cuál cara está intersectando y ajusta las coordenadas UV.
-        if (point.x - self.min.x).abs() < 1e-4 { // Cara izquierda
-            u = (point.z - self.min.z) / (self.max.z - self.min.z);
-            v = (point.y - self.min.y) / (self.max.y - self.min.y);
-        } else if (point.x - self.max.x).abs() < 1e-4 { // Cara derecha
-            u = (point.z - self.min.z) / (self.max.z - self.min.z);
-            v = (point.y - self.min.y) / (self.max.y - self.min.y);
-        } else if (point.y - self.min.y).abs() < 1e-4 { // Cara inferior
-            u = (point.x - self.min.x) / (self.max.x - self.min.x);
-            v = (point.z - self.min.z) / (self.max.z - self.min.z);
-        } else if (point.y - self.max.y).abs() < 1e-4 { // Cara superior
-            u = (point.x - self.min.x) / (self.max.x - self.min.x);
-            v = (point.z - self.min.z) / (self.max.z - self.min.z);
-        } else if (point.z - self.min.z).abs() < 1e-4 { // Cara trasera
-            u = (point.x - self.min.x) / (self.max.x - self.min.x);
-            v = (point.y - self.min.y) / (self.max.y - self.min.y);
-        } else if (point.z - self.max.z).abs() < 1e-4 { // Cara delantera
-            u = (point.x - self.min.x) / (self.max.x - self.min.x);
-            v = (point.y - self.min.y) / (self.max.y - self.min.y);
+    pub fn new(min: Vec3, max: Vec3, material: Material) -> Self {
+        Cube::with_face_materials(min, max, FaceMaterials::uniform(material))
+    }
+
+    pub fn with_face_materials(min: Vec3, max: Vec3, materials: FaceMaterials) -> Self {
+        Cube { min, max, materials }
+    }
+
+    // Cara sobre la que está un punto de la superficie
+    fn face_at(&self, point: &Vec3) -> Face {
+        if (point.x - self.min.x).abs() < 1e-4 {
+            Face::Left
+        } else if (point.x - self.max.x).abs() < 1e-4 {
+            Face::Right
+        } else if (point.y - self.min.y).abs() < 1e-4 {
+            Face::Bottom
+        } else if (point.y - self.max.y).abs() < 1e-4 {
+            Face::Top
+        } else if (point.z - self.min.z).abs() < 1e-4 {
+            Face::Back
+        } else {
+            Face::Front
         }
+    }
 
-        (u, v)
+    // UV por unidad de distancia en una cara (la textura cubre la cara entera)
+    fn uv_scale(&self, face: Face) -> f32 {
+        let size = self.max - self.min;
+        let area = match face.axis() {
+            0 => size.z * size.y,
+            1 => size.x * size.z,
+            _ => size.x * size.y,
+        };
+        1.0 / area.sqrt()
+    }
+
+    // Obtén las coordenadas UV en la cara intersectada
+    fn get_uv(&self, point: &Vec3, face: Face) -> (f32, f32) {
+        let local = (point - self.min).component_div(&(self.max - self.min));
+        match face.axis() {
+            0 => (local.z, local.y), // Caras izquierda y derecha
+            1 => (local.x, local.z), // Caras inferior y superior
+            _ => (local.x, local.y), // Caras trasera y delantera
+        }
+    }
+
+    // Intersección a distancia t; None si cae en un hueco recortado por alfa.
+    // Tras cruzar un hueco el cubo es un cascarón y su cara de salida se ve desde adentro, así
+    // que su normal apunta hacia el cubo (`through_hole`).
+    fn hit_at(&self, ray_origin: &Vec3, ray_direction: &Vec3, t: f32, through_hole: bool) -> Option<Intersect> {
+        let point = ray_origin + ray_direction * t;
+        let face = self.face_at(&point);
+        let (u, v) = self.get_uv(&point, face);
+        let material = self.materials.get(face);
+        if material.is_cutout_at(u, v) {
+            return None;
+        }
+        let (tangent, bitangent) = face.tangents();
+        let normal = material.perturb_normal(&face.normal(), &tangent, &bitangent, u, v);
+        let normal = if through_hole { -normal } else { normal };
+        Some(
+            Intersect::new(point, normal, t, material.clone(), u, v)
+                .with_face(face)
+                .with_uv_scale(self.uv_scale(face)),
+        )
     }
 }
 
-impl RayIntersect for Cube {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+impl Cube {
+    // Distancias de entrada y salida del rayo a las tres losas del cubo
+    fn slabs(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> (f32, f32) {
         // Inverso de la dirección del rayo
         let inv_dir = Vec3::new(
             if ray_direction.x != 0.0 { 1.0 / ray_direction.x } else { f32::INFINITY },
@@ -59,37 +128,37 @@ impl RayIntersect for Cube {
         let t2 = max2(&t_min, &t_max);
 
         // Encontrar el t_near y t_far
-        let t_near = t1.x.max(t1.y).max(t1.z);
-        let t_far = t2.x.min(t2.y).min(t2.z);
+        (t1.x.max(t1.y).max(t1.z), t2.x.min(t2.y).min(t2.z))
+    }
+}
+
+impl RayIntersect for Cube {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let (t_near, t_far) = self.slabs(ray_origin, ray_direction);
 
         // Si el rayo no intersecta el cubo
-        if t_near < 0.0 || t_near > t_far {
+        if t_far < 0.0 || t_near > t_far {
             return Intersect::empty();
         }
 
-        // Calcular el punto de intersección y normal
-        let intersection_point = ray_origin + ray_direction * t_near;
-
-        // Para encontrar la normal de la cara intersectada
-        let normal = if (intersection_point.x - self.min.x).abs() < 1e-4 {
-            Vec3::new(-1.0, 0.0, 0.0) // Cara izquierda
-        } else if (intersection_point.x - self.max.x).abs() < 1e-4 {
-            Vec3::new(1.0, 0.0, 0.0) // Cara derecha
-        } else if (intersection_point.y - self.min.y).abs() < 1e-4 {
-            Vec3::new(0.0, -1.0, 0.0) // Cara inferior
-        } else if (intersection_point.y - self.max.y).abs() < 1e-4 {
-            Vec3::new(0.0, 1.0, 0.0) // Cara superior
-        } else if (intersection_point.z - self.min.z).abs() < 1e-4 {
-            Vec3::new(0.0, 0.0, -1.0) // Cara trasera
-        } else {
-            Vec3::new(0.0, 0.0, 1.0) // Cara delantera
-        };
+        // Un rayo que nace adentro (refractado) sale del sólido por la cara lejana. Conserva la
+        // normal exterior: con ella `cos_i > 0` indica la salida, como esperan refract y Fresnel.
+        if t_near < 0.0 {
+            return self.hit_at(ray_origin, ray_direction, t_far, false).unwrap_or_else(Intersect::empty);
+        }
+
+        // Si la cara de entrada es un hueco recortado, el rayo sigue hasta la cara de salida
+        self.hit_at(ray_origin, ray_direction, t_near, false)
+            .or_else(|| self.hit_at(ray_origin, ray_direction, t_far, true))
+            .unwrap_or_else(Intersect::empty)
+    }
 
-        let normal = normal.normalize();
-        // Calcular las coordenadas UV en la cara donde se dio la intersección
-        let (u, v) = self.get_uv(&intersection_point);
+    fn exit_distance(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let (t_near, t_far) = self.slabs(ray_origin, ray_direction);
+        (t_near <= 0.0 && t_far > 0.0).then_some(t_far)
+    }
 
-        // Retornar la intersección con toda la información
-        Intersect::new(intersection_point, normal, t_near, self.material.clone(), u, v)
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.min, self.max)
     }
 }
@@ -1,4 +1,11 @@
 // framebuffer.rs
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use image::{ExtendedColorType, ImageEncoder, ImageResult};
+use image::codecs::pnm::{PnmEncoder, PnmSubtype, SampleEncoding};
+use crate::color::Color;
+use crate::tonemap::ToneMapper;
 
 pub struct Framebuffer {
     pub width: usize,
@@ -6,6 +13,8 @@ pub struct Framebuffer {
     pub buffer: Vec<u32>,
     background_color: u32,
     current_color: u32,
+    // Conversión de radiancia HDR a los píxeles de 8 bits del buffer
+    pub tone_mapper: ToneMapper,
 }
 
 impl Framebuffer {
@@ -16,6 +25,7 @@ impl Framebuffer {
             buffer: vec![0; width * height],
             background_color: 0x000000,
             current_color: 0xFFFFFF,
+            tone_mapper: ToneMapper::default(),
         }
     }
 
@@ -38,4 +48,47 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
+
+    // Escribe un píxel a partir de radiancia lineal, aplicando exposición, curva y gamma
+    pub fn set_pixel_color(&mut self, x: usize, y: usize, color: Color) {
+        if x < self.width && y < self.height {
+            self.buffer[y * self.width + x] = self.tone_mapper.to_hex(color);
+        }
+    }
+
+    // Píxeles en orden RGB de 8 bits, fila por fila
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        self.buffer
+            .iter()
+            .flat_map(|pixel| [(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8])
+            .collect()
+    }
+
+    // Guarda el contenido en un archivo; el formato (PNG, PPM...) se deduce de la extensión
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        let path = path.as_ref();
+        let is_ppm = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ppm"));
+
+        if is_ppm {
+            // El codificador PNM por defecto escribe PAM (P7); forzamos PPM binario (P6)
+            let file = BufWriter::new(File::create(path)?);
+            let encoder = PnmEncoder::new(file).with_subtype(PnmSubtype::Pixmap(SampleEncoding::Binary));
+            return encoder.write_image(
+                &self.to_rgb_bytes(),
+                self.width as u32,
+                self.height as u32,
+                ExtendedColorType::Rgb8,
+            );
+        }
+
+        image::save_buffer(
+            path,
+            &self.to_rgb_bytes(),
+            self.width as u32,
+            self.height as u32,
+            ExtendedColorType::Rgb8,
+        )
+    }
 }
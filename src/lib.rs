@@ -0,0 +1,24 @@
+pub mod framebuffer;
+pub mod ray_intersect;
+pub mod color;
+pub mod camera;
+pub mod light;
+pub mod sky;
+pub mod material;
+pub mod brdf;
+pub mod texture;
+pub mod atlas;
+pub mod assets;
+pub mod castray;
+pub mod pathtrace;
+pub mod cube;
+pub mod sphere;
+pub mod square;
+pub mod scene;
+pub mod bvh;
+pub mod render;
+pub mod scene_loader;
+pub mod voxel;
+pub mod tonemap;
+pub mod sampling;
+pub mod accumulator;
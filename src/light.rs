@@ -1,18 +1,431 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
 use nalgebra_glm::Vec3;
 use crate::color::Color;
+use crate::ray_intersect::Face;
+use crate::sampling::Rng;
+use crate::texture::Texture;
 
+// Diámetro aparente del sol visto desde la Tierra, en radianes (~0.53°)
+pub const SUN_ANGULAR_DIAMETER: f32 = 0.0093;
+// Distancia a la que un punto se considera sobre la superficie de una luz de área
+const SURFACE_TOLERANCE: f32 = 1e-3;
+
+// Cómo se debilita con la distancia la luz de las fuentes con posición
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Falloff {
+    // Llega igual a cualquier distancia
+    None,
+    // Físico: 1 / d²
+    InverseSquare,
+    // Se apaga suavemente hasta llegar a cero en el alcance dado
+    Range(f32),
+}
+
+impl Falloff {
+    pub fn at(self, distance: f32) -> f32 {
+        match self {
+            Falloff::None => 1.0,
+            Falloff::InverseSquare => 1.0 / (distance * distance).max(1e-4),
+            Falloff::Range(range) => {
+                let x = (distance / range).min(1.0);
+                (1.0 - x * x).powi(2)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LightKind {
+    // Emite en todas direcciones desde un punto
+    Point { position: Vec3, falloff: Falloff },
+    // Rayos paralelos que llegan desde `direction` (hacia la luz), como el sol o la luna.
+    // El diámetro angular, en radianes, da el tamaño de la penumbra.
+    Directional { direction: Vec3, angular_diameter: f32 },
+    // Foco en `position` que apunta hacia `direction`. Ilumina por completo dentro de
+    // `inner_angle` y se apaga hasta `outer_angle` (ángulos desde el eje, en radianes).
+    // El `cookie` es una imagen proyectada que tiñe la luz, como una diapositiva.
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+        falloff: Falloff,
+        cookie: Option<Arc<Texture>>,
+    },
+    // Panel rectangular con lados `edge_u` y `edge_v`; emite solo hacia edge_u × edge_v
+    Rect { center: Vec3, edge_u: Vec3, edge_v: Vec3, falloff: Falloff },
+    // Esfera que emite desde toda su superficie
+    Sphere { center: Vec3, radius: f32, falloff: Falloff },
+    // Caja alineada a los ejes que emite desde sus caras, como un bloque de lava
+    Cube { min: Vec3, max: Vec3, falloff: Falloff },
+}
+
+#[derive(Debug, Clone)]
 pub struct Light {
-    pub position: Vec3,
+    pub kind: LightKind,
     pub color: Color,
     pub intensity: f32,
+    // Rayos de sombra por punto sombreado; las luces con área necesitan varios para una
+    // penumbra sin ruido
+    pub samples: u32,
+}
+
+// Dos ejes perpendiculares a `axis` (unitario), con el primero horizontal si se puede
+fn basis(axis: &Vec3) -> (Vec3, Vec3) {
+    let reference = if axis.y.abs() < 0.999 { Vec3::y() } else { Vec3::z() };
+    let tangent = reference.cross(axis).normalize();
+    (tangent, axis.cross(&tangent))
 }
 
 impl Light {
+    // Luz puntual, sin atenuación hasta que se le dé un `with_falloff`
     pub fn new(position: Vec3, color: Color, intensity: f32) -> Self {
         Light {
-            position,
+            kind: LightKind::Point { position, falloff: Falloff::None },
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    pub fn directional(direction: Vec3, color: Color, intensity: f32, angular_diameter: f32) -> Self {
+        Light {
+            kind: LightKind::Directional {
+                direction: direction.normalize(),
+                angular_diameter,
+            },
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    pub fn spot(position: Vec3, direction: Vec3, color: Color, intensity: f32, inner_angle: f32, outer_angle: f32) -> Self {
+        Light {
+            kind: LightKind::Spot {
+                position,
+                direction: direction.normalize(),
+                inner_angle,
+                outer_angle,
+                falloff: Falloff::None,
+                cookie: None,
+            },
             color,
             intensity,
+            samples: 1,
+        }
+    }
+
+    // Panel de `width` × `height` centrado en `center` que ilumina hacia `normal`
+    pub fn rect(center: Vec3, normal: Vec3, width: f32, height: f32, color: Color, intensity: f32) -> Self {
+        let (tangent, bitangent) = basis(&normal.normalize());
+        Light::rect_with_edges(center, tangent * width, bitangent * height, color, intensity)
+    }
+
+    // Panel con los lados dados, para ajustarlo a una cara; ilumina hacia edge_u × edge_v
+    pub fn rect_with_edges(center: Vec3, edge_u: Vec3, edge_v: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Rect { center, edge_u, edge_v, falloff: Falloff::None },
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    // Panel sobre la cara `face` de la caja [min, max], que ilumina hacia afuera de ella. La caja
+    // puede ser plana sobre el eje de la cara.
+    pub fn box_face(min: Vec3, max: Vec3, face: Face, color: Color, intensity: f32) -> Self {
+        let (axis, normal) = (face.axis(), face.normal());
+        let size = max - min;
+        let center = (min + max) * 0.5 + normal * (size[axis] * 0.5);
+        let (mut edge_u, mut edge_v) = (Vec3::zeros(), Vec3::zeros());
+        edge_u[(axis + 1) % 3] = size[(axis + 1) % 3];
+        edge_v[(axis + 2) % 3] = size[(axis + 2) % 3];
+        // El panel ilumina hacia edge_u × edge_v, que debe ser la normal exterior de la cara
+        if edge_u.cross(&edge_v).dot(&normal) < 0.0 {
+            edge_u = -edge_u;
+        }
+        Light::rect_with_edges(center, edge_u, edge_v, color, intensity)
+    }
+
+    pub fn sphere(center: Vec3, radius: f32, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Sphere { center, radius, falloff: Falloff::None },
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    pub fn cube(min: Vec3, max: Vec3, color: Color, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Cube { min, max, falloff: Falloff::None },
+            color,
+            intensity,
+            samples: 1,
+        }
+    }
+
+    // Las luces direccionales no tienen distancia y lo ignoran
+    pub fn with_falloff(mut self, falloff: Falloff) -> Self {
+        match &mut self.kind {
+            LightKind::Point { falloff: current, .. }
+            | LightKind::Spot { falloff: current, .. }
+            | LightKind::Rect { falloff: current, .. }
+            | LightKind::Sphere { falloff: current, .. }
+            | LightKind::Cube { falloff: current, .. } => *current = falloff,
+            LightKind::Directional { .. } => {}
+        }
+        self
+    }
+
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    // Solo los focos proyectan una imagen
+    pub fn with_cookie(mut self, texture: Arc<Texture>) -> Self {
+        if let LightKind::Spot { cookie, .. } = &mut self.kind {
+            *cookie = Some(texture);
+        }
+        self
+    }
+
+    // Dirección hacia el centro de la luz desde `point` y la distancia hasta ella
+    pub fn direction_from(&self, point: &Vec3) -> (Vec3, f32) {
+        match &self.kind {
+            LightKind::Directional { direction, .. } => (*direction, f32::INFINITY),
+            _ => {
+                let offset = self.center() - point;
+                (offset.normalize(), offset.magnitude())
+            }
+        }
+    }
+
+    // Posición de la luz, o el centro de su forma; las direccionales no tienen
+    fn center(&self) -> Vec3 {
+        match &self.kind {
+            LightKind::Point { position, .. } | LightKind::Spot { position, .. } => *position,
+            LightKind::Rect { center, .. } | LightKind::Sphere { center, .. } => *center,
+            LightKind::Cube { min, max, .. } => (min + max) * 0.5,
+            LightKind::Directional { .. } => Vec3::zeros(),
+        }
+    }
+
+    // Fracción de la intensidad que llega a `point` desde el centro de la luz
+    pub fn attenuation(&self, point: &Vec3) -> f32 {
+        let (direction, distance) = self.direction_from(point);
+        self.attenuation_along(point, &direction, distance)
+    }
+
+    // Fracción de la intensidad que llega a `point` desde el punto de la luz que está en
+    // `direction` a `distance`: la distancia, el cono de los focos y el ángulo con que se ve un
+    // panel (que emite como una superficie mate)
+    pub fn attenuation_along(&self, point: &Vec3, direction: &Vec3, distance: f32) -> f32 {
+        match &self.kind {
+            LightKind::Point { falloff, .. } | LightKind::Sphere { falloff, .. } | LightKind::Cube { falloff, .. } => {
+                falloff.at(distance)
+            }
+            LightKind::Directional { .. } => 1.0,
+            LightKind::Rect { edge_u, edge_v, falloff, .. } => {
+                let cos_emit = -direction.dot(&edge_u.cross(edge_v).normalize());
+                cos_emit.max(0.0) * falloff.at(distance)
+            }
+            LightKind::Spot { position, direction: axis, inner_angle, outer_angle, falloff, .. } => {
+                let offset = point - position;
+                let cos_angle = offset.normalize().dot(axis);
+                let (cos_inner, cos_outer) = (inner_angle.cos(), outer_angle.cos());
+                let t = ((cos_angle - cos_outer) / (cos_inner - cos_outer).max(1e-6)).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t) * falloff.at(distance)
+            }
         }
     }
+
+    // Filtro del cookie proyectado sobre `point`; blanco en las luces que no tienen
+    pub fn cookie_at(&self, point: &Vec3) -> Color {
+        let LightKind::Spot { position, direction, outer_angle, cookie: Some(cookie), .. } = &self.kind else {
+            return Color::white();
+        };
+        let offset = point - position;
+        let depth = offset.dot(direction);
+        if depth <= 0.0 {
+            return Color::black();
+        }
+
+        // La imagen queda derecha respecto al eje y del mundo y cubre el cono exterior
+        let reference = if direction.y.abs() < 0.99 { Vec3::y() } else { Vec3::z() };
+        let right = direction.cross(&reference).normalize();
+        let up = right.cross(direction);
+        let scale = 2.0 * depth * outer_angle.tan();
+        let u = 0.5 + offset.dot(&right) / scale;
+        let v = 0.5 + offset.dot(&up) / scale;
+        cookie.sample(u, v)
+    }
+
+    // Como direction_from, pero a un punto al azar de la superficie de la luz (sombras suaves)
+    pub fn sample_direction(&self, point: &Vec3, rng: &mut Rng) -> (Vec3, f32) {
+        match self.kind {
+            LightKind::Directional { direction, angular_diameter } if angular_diameter > 0.0 => {
+                // Uniforme dentro del cono que subtiende el disco
+                let cos_max = (angular_diameter / 2.0).cos();
+                let cos_theta = 1.0 - rng.next_f32() * (1.0 - cos_max);
+                let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+                let phi = 2.0 * PI * rng.next_f32();
+
+                let (tangent, bitangent) = basis(&direction);
+                let sampled = tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + direction * cos_theta;
+                (sampled.normalize(), f32::INFINITY)
+            }
+            // Desde atrás de un panel, o dentro de la forma, no hay superficie que muestrear
+            _ => match self.sample_surface(point, rng) {
+                Some((direction, distance, _)) => (direction, distance),
+                None => self.direction_from(point),
+            },
+        }
+    }
+
+    // Para el trazador de caminos: dirección hacia un punto al azar de la superficie de una luz
+    // de área, la distancia y la densidad por ángulo sólido con que se eligió. Las luces sin
+    // área, o las que no se ven desde `point`, devuelven None.
+    pub fn sample_surface(&self, point: &Vec3, rng: &mut Rng) -> Option<(Vec3, f32, f32)> {
+        let target = match self.kind {
+            LightKind::Rect { center, edge_u, edge_v, .. } => {
+                center + edge_u * (rng.next_f32() - 0.5) + edge_v * (rng.next_f32() - 0.5)
+            }
+            LightKind::Sphere { center, radius, .. } => {
+                // Uniforme dentro del cono que subtiende la esfera, hasta su cara cercana
+                let offset = center - point;
+                let distance = offset.magnitude();
+                if distance <= radius {
+                    return None;
+                }
+                let one_minus_cos_max = sphere_cone(radius, distance);
+                let cos_theta = 1.0 - rng.next_f32() * one_minus_cos_max;
+                let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+                let phi = 2.0 * PI * rng.next_f32();
+
+                let axis = offset / distance;
+                let (tangent, bitangent) = basis(&axis);
+                let direction = (tangent * (sin_theta * phi.cos()) + bitangent * (sin_theta * phi.sin()) + axis * cos_theta).normalize();
+                let along = distance * cos_theta;
+                let depth = (radius * radius - distance * distance * sin_theta * sin_theta).max(0.0).sqrt();
+                return Some((direction, along - depth, 1.0 / (2.0 * PI * one_minus_cos_max)));
+            }
+            LightKind::Cube { min, max, .. } => sample_cube_face(&min, &max, point, rng),
+            _ => return None,
+        };
+        let pdf = self.surface_pdf(point, &target)?;
+        let offset = target - point;
+        Some((offset.normalize(), offset.magnitude(), pdf))
+    }
+
+    // Densidad por ángulo sólido con que `sample_surface` elegiría `target` visto desde `point`;
+    // None si `target` no está sobre la parte visible de esta luz
+    pub fn surface_pdf(&self, point: &Vec3, target: &Vec3) -> Option<f32> {
+        let direction = (target - point).normalize();
+        match &self.kind {
+            LightKind::Rect { center, edge_u, edge_v, .. } => {
+                let normal = edge_u.cross(edge_v);
+                let area = normal.magnitude();
+                let normal = normal / area;
+                let local = target - center;
+                let inside = local.dot(&normal).abs() < SURFACE_TOLERANCE
+                    && local.dot(edge_u).abs() <= edge_u.magnitude_squared() * 0.5 + SURFACE_TOLERANCE
+                    && local.dot(edge_v).abs() <= edge_v.magnitude_squared() * 0.5 + SURFACE_TOLERANCE;
+                if !inside {
+                    return None;
+                }
+                area_to_solid_angle(point, target, area, -direction.dot(&normal))
+            }
+            LightKind::Sphere { center, radius, .. } => {
+                let distance = (center - point).magnitude();
+                let on_surface = ((target - center).magnitude() - radius).abs() < SURFACE_TOLERANCE * radius.max(1.0);
+                if distance <= *radius || !on_surface {
+                    return None;
+                }
+                Some(1.0 / (2.0 * PI * sphere_cone(*radius, distance)))
+            }
+            LightKind::Cube { min, max, .. } => {
+                let (faces, total_area) = visible_faces(min, max, point);
+                let within = |axis: usize| {
+                    (0..3).all(|other| {
+                        other == axis || (target[other] >= min[other] - SURFACE_TOLERANCE && target[other] <= max[other] + SURFACE_TOLERANCE)
+                    })
+                };
+                faces
+                    .iter()
+                    .find(|(axis, side, area)| *area > 0.0 && (target[*axis] - side).abs() < SURFACE_TOLERANCE && within(*axis))
+                    .and_then(|(axis, _, _)| area_to_solid_angle(point, target, total_area, direction[*axis].abs()))
+            }
+            _ => None,
+        }
+    }
+}
+
+
+// 1 - cos del semiángulo del cono que subtiende una esfera de radio `radius` a `distance` de su
+// centro, escrito de modo que no se pierda precisión con esferas lejanas
+fn sphere_cone(radius: f32, distance: f32) -> f32 {
+    let ratio = (radius / distance).powi(2);
+    ratio / (1.0 + (1.0 - ratio).max(0.0).sqrt())
+}
+
+// Caras de la caja que miran hacia `point` (eje, coordenada de la cara y área) y su área total
+fn visible_faces(min: &Vec3, max: &Vec3, point: &Vec3) -> ([(usize, f32, f32); 3], f32) {
+    let size = max - min;
+    let mut faces = [(0, 0.0, 0.0); 3];
+    let mut total_area = 0.0;
+    for (axis, face) in faces.iter_mut().enumerate() {
+        let side = if point[axis] < min[axis] {
+            min[axis]
+        } else if point[axis] > max[axis] {
+            max[axis]
+        } else {
+            continue;
+        };
+        let area = size[(axis + 1) % 3] * size[(axis + 2) % 3];
+        *face = (axis, side, area);
+        total_area += area;
+    }
+    (faces, total_area)
+}
+
+// Punto al azar de las caras de la caja que miran hacia `point`, con probabilidad según el área
+fn sample_cube_face(min: &Vec3, max: &Vec3, point: &Vec3, rng: &mut Rng) -> Vec3 {
+    let (faces, total_area) = visible_faces(min, max, point);
+    if total_area <= 0.0 {
+        // El punto está dentro de la caja
+        return (min + max) * 0.5;
+    }
+
+    let mut pick = rng.next_f32() * total_area;
+    let (mut axis, mut side) = (0, 0.0);
+    for (face_axis, face_side, area) in faces {
+        if area > 0.0 {
+            (axis, side) = (face_axis, face_side);
+            pick -= area;
+            if pick <= 0.0 {
+                break;
+            }
+        }
+    }
+    let size = max - min;
+    let mut target = Vec3::new(
+        min.x + size.x * rng.next_f32(),
+        min.y + size.y * rng.next_f32(),
+        min.z + size.z * rng.next_f32(),
+    );
+    target[axis] = side;
+    target
+}
+
+// Densidad por ángulo sólido de un punto elegido uniformemente en un área `area` cuya normal
+// forma `cos_emit` con la dirección hacia `point`
+fn area_to_solid_angle(point: &Vec3, target: &Vec3, area: f32, cos_emit: f32) -> Option<f32> {
+    if cos_emit <= 1e-6 || area <= 0.0 {
+        return None;
+    }
+    Some((target - point).magnitude_squared() / (area * cos_emit))
 }
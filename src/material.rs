@@ -1,10 +1,20 @@
-use once_cell::sync::Lazy;
 use std::sync::Arc;
 use nalgebra_glm::Vec3;
 
+use crate::atlas::Tile;
+use crate::brdf::SurfaceSample;
 use crate::color::Color;
 use crate::texture::Texture;
 
+// Parámetros del modelo metálico-rugoso. Cada mapa (lineal, canal rojo) multiplica a su factor,
+// igual que en glTF; el color base es `diffuse` o la textura del material.
+#[derive(Debug, Clone)]
+pub struct Pbr {
+  pub metallic: f32,
+  pub roughness: f32,
+  pub metallic_map: Option<Arc<Texture>>,
+  pub roughness_map: Option<Arc<Texture>>,
+}
 
 #[derive(Debug, Clone)]
 pub struct Material {
@@ -15,9 +25,16 @@ pub struct Material {
   pub has_texture: bool,
   pub has_normal_map: bool,
   pub texture: Option<Arc<Texture>>,
+  pub normal_map: Option<Arc<Texture>>, // Mapa de normales en espacio tangente (cargado lineal)
+  pub normal_strength: f32,       // 0 = normal geométrica, 1 = la del mapa tal cual
+  pub tile: Option<Tile>,         // Región de la textura (mosaico de un atlas); None = toda
+  pub alpha_cutoff: Option<f32>,  // Recorte por alfa: los texeles con menos opacidad no existen
   pub emission: Color,            // Color de la emisión
   pub emission_strength: f32,     // Intensidad de la emisión
-  pub has_emission: bool, // Si el material usa una textura para la emisión
+  pub has_emission: bool, // Si el material emite luz (de la textura, o de `emission` si no tiene)
+  pub pbr: Option<Pbr>,   // Modelo físico; None = Phong clásico con `albedo`
+  pub absorption: Color,  // Coeficiente de Beer-Lambert por unidad de distancia dentro del objeto
+  pub roughness: f32,     // Rugosidad de reflejos y refracciones del modelo clásico (0 = espejo)
 }
 
 impl Material {
@@ -35,10 +52,17 @@ impl Material {
       refractive_index,
       has_texture: false,
       has_normal_map: false,
+      normal_map: None,
+      normal_strength: 1.0,
       texture: None,
-      emission: Color::new(0, 0, 0),
+      tile: None,
+      alpha_cutoff: None,
+      emission: Color::black(),
       emission_strength: 0.0,
       has_emission: false,
+      pbr: None,
+      absorption: Color::black(),
+      roughness: 0.0,
     }
   }
 
@@ -51,36 +75,51 @@ impl Material {
     emission_strength: f32,
 ) -> Self {
     Material {
-        diffuse: Color::new(255, 255, 255), // Color difuso por defecto
+        diffuse: Color::white(), // Color difuso por defecto
         specular,
         albedo,
         refractive_index,
         has_texture: true,
         has_normal_map: false,
+        normal_map: None,
+        normal_strength: 1.0,
         texture: Some(texture),
+        tile: None,
+        alpha_cutoff: None,
         emission,
         emission_strength,
         has_emission: true,
+        pbr: None,
+        absorption: Color::black(),
+        roughness: 0.0,
     }
 }
 
-pub fn get_emission_color(&self, u: f32, v: f32) -> Color {
-  if self.has_emission {
+pub fn get_emission_color(&self, u: f32, v: f32, footprint: f32) -> Color {
+  if !self.has_emission {
+      return Color::black();
+  }
+  if self.has_texture {
       // Si el material tiene una textura de emisión, tomar el color de la textura
-      let texture = self.texture.as_ref().unwrap();
-      let x = (u * (texture.width as f32 - 1.0)) as usize;
-      let y = ((1.0 - v) * (texture.height as f32 - 1.0)) as usize;
-      let tex_color = texture.get_color(x, y);
-      Color::new(
-          (tex_color.r as f32 * self.emission_strength) as u8,
-          (tex_color.g as f32 * self.emission_strength) as u8,
-          (tex_color.b as f32 * self.emission_strength) as u8,
-      )
+      self.texel(u, v, footprint) * self.emission_strength
   } else {
-      self.emission
+      self.emission * self.emission_strength
   }
 }
 
+  // Emisión promedio sobre toda la superficie (el último nivel de mipmap de la textura)
+  pub fn average_emission(&self) -> Color {
+    self.get_emission_color(0.5, 0.5, 1.0)
+  }
+
+  // Hace que el material emita luz: `color` sin textura, o la textura misma si tiene
+  pub fn with_emission(mut self, color: Color, strength: f32) -> Self {
+    self.has_emission = true;
+    self.emission = color;
+    self.emission_strength = strength;
+    self
+  }
+
   pub fn new_with_texture(
     specular: f32,
     albedo: [f32; 4],
@@ -89,63 +128,210 @@ pub fn get_emission_color(&self, u: f32, v: f32) -> Color {
 
   ) -> Self {
     Material {
-      diffuse: Color::new(255, 255, 255), // Color difuso por defecto
+      diffuse: Color::white(), // Color difuso por defecto
       specular,
       albedo,
       refractive_index,
       has_texture: true,
       has_normal_map: false,
+      normal_map: None,
+      normal_strength: 1.0,
       texture: Some(texture),
-      emission: Color::new(0, 0, 0),
+      tile: None,
+      alpha_cutoff: None,
+      emission: Color::black(),
       emission_strength: 0.0,
       has_emission: false,
+      pbr: None,
+      absorption: Color::black(),
+      roughness: 0.0,
     }
   }
 
-  pub fn get_diffuse_color(&mut self, u: f32, v: f32) -> Color {
+  // `footprint`: ancho en UV del píxel sobre la superficie, para elegir el mipmap (0 = sin filtrar)
+  pub fn get_diffuse_color(&self, u: f32, v: f32, footprint: f32) -> Color {
     if self.has_texture {
-      let texture = self.texture.as_ref().unwrap();
-      let x = (u * (texture.width as f32 - 1.0)) as usize;
-      let y = ((1.0 - v) * (texture.height as f32 - 1.0)) as usize;
-      texture.get_color(x, y)
-      // Color::new(255, 0, 0)
+      self.texel(u, v, footprint)
+      // Color::from_rgb8(255, 0, 0)
     }
     else {
       self.diffuse
     }
   }
 
+  // Normal del mapa en espacio tangente (x sobre u, y sobre v, z fuera de la superficie)
   pub fn get_normal_from_map(&self, u: f32, v: f32) -> Vec3 {
-    if self.has_normal_map {
-      let texture = self.texture.as_ref().unwrap();
-      let x = (u * (texture.width as f32 - 1.0)) as usize;
-      let y = ((1.0 - v) * (texture.height as f32 - 1.0)) as usize;
-      let texture = self.texture.as_ref().unwrap();
-      let color = texture.get_color(x, y);
-    
-      // Correctly decode the normal map
-      let nx = (color.r as f32 / 255.0) * 2.0 - 1.0;
-      let ny = (color.g as f32 / 255.0) * 2.0 - 1.0;
-      let nz = color.b as f32 / 255.0; // Note: only 0 to 1 range for Z
+    match &self.normal_map {
+      Some(normal_map) if self.has_normal_map => {
+        let color = normal_map.sample(u, v);
+        let normal = Vec3::new(color.r * 2.0 - 1.0, color.g * 2.0 - 1.0, color.b * 2.0 - 1.0);
+        // Un texel corrupto (o un tablero de reemplazo) no debe voltear la superficie
+        if normal.z <= 0.0 {
+          return Vec3::new(0.0, 0.0, 1.0);
+        }
+        normal.normalize()
+      }
+      _ => Vec3::new(0.0, 0.0, 1.0),
+    }
+  }
 
-      Vec3::new(nx, ny, nz).normalize()
+  // Aplica el mapa de normales en (u, v). `tangent` y `bitangent` son las direcciones en que
+  // crecen u y v sobre la superficie; sin mapa devuelve la normal geométrica.
+  pub fn perturb_normal(&self, normal: &Vec3, tangent: &Vec3, bitangent: &Vec3, u: f32, v: f32) -> Vec3 {
+    if !self.has_normal_map {
+      return *normal;
+    }
+    let mapped = self.get_normal_from_map(u, v);
+    let perturbed = (tangent * mapped.x + bitangent * mapped.y) * self.normal_strength + normal * mapped.z;
+    let perturbed = perturbed.normalize();
+    if perturbed.dot(normal) > 0.0 {
+      perturbed
     } else {
-      Vec3::new(0.0, 0.0, 1.0) // Default normal if no normal map is present
+      *normal
+    }
+  }
+
+  // Usa un mapa de normales propio, independiente de la textura de color
+  pub fn with_normal_map(mut self, normal_map: Arc<Texture>, strength: f32) -> Self {
+    self.has_normal_map = true;
+    self.normal_map = Some(normal_map);
+    self.normal_strength = strength;
+    self
+  }
+
+  // Usa solo un mosaico de la textura, p. ej. un bloque dentro de un atlas
+  pub fn with_tile(mut self, tile: Tile) -> Self {
+    self.tile = Some(tile);
+    self
+  }
+
+  // Activa el recorte por alfa: los rayos atraviesan los texeles con opacidad menor a `cutoff`
+  pub fn with_alpha_cutout(mut self, cutoff: f32) -> Self {
+    self.alpha_cutoff = Some(cutoff);
+    self
+  }
+
+  // El punto (u, v) es un hueco recortado por alfa
+  pub fn is_cutout_at(&self, u: f32, v: f32) -> bool {
+    match (self.alpha_cutoff, &self.texture) {
+      (Some(cutoff), Some(texture)) => {
+        let tile = self.tile.unwrap_or_else(|| Tile::full(texture));
+        texture.sample_alpha(u, v, &tile) < cutoff
+      }
+      _ => false,
+    }
+  }
+
+  // Color de la textura en (u, v), dentro del mosaico si lo hay
+  fn texel(&self, u: f32, v: f32, footprint: f32) -> Color {
+    let texture = self.texture.as_ref().unwrap();
+    let tile = self.tile.unwrap_or_else(|| Tile::full(texture));
+    texture.sample_filtered(u, v, &tile, footprint)
+  }
+
+  // Texel de un mapa de datos que acompaña a la textura: si el material usa un mosaico, el mapa
+  // se lee en la misma región, escalada por si el mapa tiene otra resolución que el atlas
+  fn map_texel(&self, map: &Texture, u: f32, v: f32, footprint: f32) -> Color {
+    let tile = match (self.tile, &self.texture) {
+      (Some(tile), Some(texture)) => Tile::new(
+        tile.x * map.width / texture.width,
+        tile.y * map.height / texture.height,
+        (tile.width * map.width / texture.width).max(1),
+        (tile.height * map.height / texture.height).max(1),
+      ),
+      _ => Tile::full(map),
+    };
+    map.sample_filtered(u, v, &tile, footprint)
+  }
+
+  // Material físico: `ior` fija el reflejo de la parte dieléctrica (1.5 = 4 %, como el plástico)
+  pub fn pbr(base_color: Color, metallic: f32, roughness: f32, ior: f32) -> Self {
+    let mut material = Material::new(base_color, 0.0, [1.0, 0.0, 0.0, 0.0], ior);
+    material.pbr = Some(Pbr {
+      metallic,
+      roughness,
+      metallic_map: None,
+      roughness_map: None,
+    });
+    material
+  }
+
+  pub fn pbr_with_texture(texture: Arc<Texture>, metallic: f32, roughness: f32, ior: f32) -> Self {
+    Material {
+      has_texture: true,
+      texture: Some(texture),
+      ..Material::pbr(Color::white(), metallic, roughness, ior)
     }
   }
 
+  // Reflejos y refracciones borrosos: 0 = espejo o vidrio pulido, 1 = casi difuso
+  pub fn with_roughness(mut self, roughness: f32) -> Self {
+    self.roughness = roughness;
+    self
+  }
+
+  // Tiñe el volumen: tras recorrer `distance` dentro del objeto, la luz conserva `tint`
+  pub fn with_tint(mut self, tint: Color, distance: f32) -> Self {
+    let coefficient = |channel: f32| -channel.max(1e-4).ln() / distance;
+    self.absorption = Color::new(coefficient(tint.r), coefficient(tint.g), coefficient(tint.b));
+    self
+  }
+
+  // Fracción de la luz que sobrevive a `distance` dentro del objeto (Beer-Lambert)
+  pub fn transmittance(&self, distance: f32) -> Color {
+    let a = self.absorption;
+    Color::new((-a.r * distance).exp(), (-a.g * distance).exp(), (-a.b * distance).exp())
+  }
+
+  pub fn with_metallic_map(mut self, map: Arc<Texture>) -> Self {
+    if let Some(pbr) = self.pbr.as_mut() {
+      pbr.metallic_map = Some(map);
+    }
+    self
+  }
+
+  pub fn with_roughness_map(mut self, map: Arc<Texture>) -> Self {
+    if let Some(pbr) = self.pbr.as_mut() {
+      pbr.roughness_map = Some(map);
+    }
+    self
+  }
+
+  // Parámetros físicos en (u, v); None si el material usa el modelo clásico
+  pub fn surface_at(&self, u: f32, v: f32, footprint: f32) -> Option<SurfaceSample> {
+    let pbr = self.pbr.as_ref()?;
+    let base_color = self.get_diffuse_color(u, v, footprint);
+    let factor = |value: f32, map: &Option<Arc<Texture>>| match map {
+      Some(map) => value * self.map_texel(map, u, v, footprint).r,
+      None => value,
+    };
+    Some(SurfaceSample {
+      base_color,
+      metallic: factor(pbr.metallic, &pbr.metallic_map).clamp(0.0, 1.0),
+      roughness: factor(pbr.roughness, &pbr.roughness_map).clamp(0.0, 1.0),
+      ior: if self.refractive_index > 0.0 { self.refractive_index } else { 1.5 },
+    })
+  }
+
   pub fn black() -> Self {
     Material {
-      diffuse: Color::new(0, 0, 0),
+      diffuse: Color::black(),
       specular: 0.0,
       albedo: [0.0, 0.0, 0.0, 0.0],
       refractive_index: 0.0,
       has_texture: false,
       texture: None,
+      tile: None,
+      alpha_cutoff: None,
       has_normal_map: false,
-      emission: Color::new(0, 0, 0),
+      normal_map: None,
+      normal_strength: 1.0,
+      emission: Color::black(),
       emission_strength: 0.0,
       has_emission: false,
+      pbr: None,
+      absorption: Color::black(),
+      roughness: 0.0,
     }
   }
 }
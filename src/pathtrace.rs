@@ -0,0 +1,240 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+
+use crate::brdf::{sample_cosine_direction, SurfaceSample};
+use crate::castray::{
+    microfacet_normal, offset_origin, reflect, reflection_weights, refract, shadow_along, RayCone, LIGHT_SURFACE_BIAS,
+};
+use crate::color::Color;
+use crate::light::Light;
+use crate::ray_intersect::Intersect;
+use crate::sampling::Rng;
+use crate::scene::Scene;
+
+// Rebotes que siempre se siguen antes de empezar con la ruleta rusa
+const MIN_BOUNCES: u32 = 3;
+// Tope de seguridad; la ruleta rusa termina casi todos los caminos mucho antes
+const MAX_BOUNCES: u32 = 64;
+// Un rayo hacia el punto muestreado de una luz que choca a esta distancia de él llegó a la luz
+const EMITTER_TOLERANCE: f32 = 1e-2;
+
+// Cómo refleja la luz la parte opaca de una superficie
+enum Bsdf {
+    // Modelo clásico: difusa de Lambert más el brillo de Phong normalizado
+    Phong { diffuse: Color, specular: f32, exponent: f32 },
+    // Modelo metálico-rugoso
+    Pbr(SurfaceSample),
+}
+
+impl Bsdf {
+    // BSDF multiplicada por el coseno con la luz; `view` y `light` salen del punto
+    fn evaluate(&self, normal: &Vec3, view: &Vec3, light: &Vec3) -> Color {
+        match self {
+            Bsdf::Phong { diffuse, specular, exponent } => {
+                let n_dot_l = normal.dot(light);
+                if n_dot_l <= 0.0 || normal.dot(view) <= 0.0 {
+                    return Color::black();
+                }
+                let mirror = reflect(&-light, normal);
+                let highlight = view.dot(&mirror).max(0.0).powf(*exponent) * (exponent + 2.0) / (2.0 * PI);
+                (*diffuse * (1.0 / PI) + Color::white() * (specular * highlight)) * n_dot_l
+            }
+            Bsdf::Pbr(surface) => surface.evaluate(normal, view, light),
+        }
+    }
+
+    // Dirección al azar para seguir el camino; el brillo de Phong se deja a la luz directa
+    fn sample(&self, normal: &Vec3, view: &Vec3, rng: &mut Rng) -> Option<Vec3> {
+        match self {
+            Bsdf::Phong { .. } => Some(sample_cosine_direction(normal, rng.next_f32(), rng.next_f32())),
+            Bsdf::Pbr(surface) => surface.sample(normal, view, [rng.next_f32(), rng.next_f32(), rng.next_f32()]),
+        }
+    }
+
+    // Densidad por ángulo sólido con que `sample` elige `light`
+    fn pdf(&self, normal: &Vec3, view: &Vec3, light: &Vec3) -> f32 {
+        match self {
+            Bsdf::Phong { .. } => normal.dot(light).max(0.0) / PI,
+            Bsdf::Pbr(surface) => surface.pdf(normal, view, light),
+        }
+    }
+}
+
+// Heurística de la potencia (β = 2) de Veach: peso de la estrategia con densidad `pdf` frente a
+// la otra con `other`
+fn power_heuristic(pdf: f32, other: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other * other);
+    if a + b <= 0.0 {
+        return 0.0;
+    }
+    a / (a + b)
+}
+
+// Densidad con que la luz directa habría elegido `point` visto desde `from`, contando la
+// probabilidad de escoger su luz; 0 si no es parte de ninguna luz de área
+fn light_pdf(lights: &[Light], from: &Vec3, point: &Vec3) -> f32 {
+    lights
+        .iter()
+        .find_map(|light| light.surface_pdf(from, point))
+        .map_or(0.0, |pdf| pdf / lights.len() as f32)
+}
+
+// Luz directa de una luz elegida al azar (estimación del siguiente evento).
+// Si el rayo hacia el punto muestreado llega a una superficie emisiva, la luz es esa superficie:
+// aporta su radiancia y se pesa con MIS frente al rebote de la BSDF, que también puede
+// encontrarla. Las luces puntuales, los focos, el sol y las luces de área sin forma visible solo
+// se alcanzan por aquí e iluminan con el mismo brillo que en el modelo clásico.
+fn direct_light(
+    intersect: &Intersect,
+    normal: &Vec3,
+    view: &Vec3,
+    bsdf: &Bsdf,
+    scene: &Scene,
+    lights: &[Light],
+    rng: &mut Rng,
+) -> Color {
+    let count = lights.len() as f32;
+    let light = &lights[((rng.next_f32() * count) as usize).min(lights.len() - 1)];
+    let point = intersect.point;
+
+    let (direction, distance, surface_pdf) = light.sample_surface(&point, rng).unwrap_or_else(|| {
+        let (direction, distance) = light.sample_direction(&point, rng);
+        (direction, distance, 0.0)
+    });
+    let value = bsdf.evaluate(normal, view, &direction);
+    if value.is_black() {
+        return Color::black();
+    }
+    let visibility = 1.0 - shadow_along(intersect, &direction, distance - LIGHT_SURFACE_BIAS, scene);
+    if visibility <= 0.0 {
+        return Color::black();
+    }
+
+    if surface_pdf > 0.0 {
+        let hit = scene.intersect(&offset_origin(intersect, &direction), &direction);
+        if hit.is_intersecting && (hit.distance - distance).abs() < EMITTER_TOLERANCE {
+            let emission = hit.material.get_emission_color(hit.u, hit.v, 0.0);
+            if !emission.is_black() {
+                let weight = power_heuristic(surface_pdf / count, bsdf.pdf(normal, view, &direction));
+                return value * emission * (visibility * weight * count / surface_pdf);
+            }
+        }
+    }
+
+    // Escala de modo que una superficie blanca mate de frente a la luz valga `intensity`
+    let light_color = light.color * light.cookie_at(&point);
+    value * light_color * (light.intensity * light.attenuation_along(&point, &direction, distance) * visibility * PI * count)
+}
+
+// Trazado de caminos Monte Carlo: sigue un único camino por rayo, rebotando según la BSDF de cada
+// superficie y sumando en cada vértice la luz directa de una luz al azar. Así llega también la
+// luz indirecta, que el trazador clásico ignora. Tras unos rebotes la ruleta rusa corta los
+// caminos que ya aportan poco, sin sesgar el resultado.
+pub fn trace_path(
+    ray_origin: &Vec3,
+    ray_direction: &Vec3,
+    scene: &Scene,
+    lights: &[Light],
+    cone: RayCone,
+    rng: &mut Rng,
+) -> Color {
+    let mut radiance = Color::black();
+    let mut throughput = Color::white();
+    let mut origin = *ray_origin;
+    let mut direction = ray_direction.normalize();
+    let mut cone = cone;
+    // Densidad con que el rebote anterior eligió `direction`, para pesar con MIS la emisión que
+    // encuentre; None desde la cámara y tras reflejos o refracciones, que la luz directa no imita
+    let mut bsdf_pdf: Option<f32> = None;
+
+    for bounce in 0..MAX_BOUNCES {
+        let intersect = scene.intersect(&origin, &direction);
+        if !intersect.is_intersecting {
+            radiance += throughput * scene.sky.color(&direction);
+            break;
+        }
+
+        cone = cone.at(intersect.distance);
+        let footprint = cone.footprint(&intersect, &direction);
+        let (u, v) = (intersect.u, intersect.v);
+
+        let emission = intersect.material.get_emission_color(u, v, footprint);
+        if !emission.is_black() {
+            let weight = bsdf_pdf.map_or(1.0, |pdf| power_heuristic(pdf, light_pdf(lights, &origin, &intersect.point)));
+            radiance += throughput * emission * weight;
+        }
+
+        let diffuse_color = intersect.material.get_diffuse_color(u, v, footprint);
+        let surface = intersect.material.surface_at(u, v, footprint);
+        let material = &intersect.material;
+        let cos_i = direction.dot(&intersect.normal);
+        let view = -direction;
+        // La cara que mira hacia el rayo, también cuando sale de un objeto
+        let normal = if cos_i < 0.0 { intersect.normal } else { -intersect.normal };
+
+        let (bsdf, reflected, transmitted) = match surface {
+            Some(surface) => (Bsdf::Pbr(surface), 0.0, 0.0),
+            None => {
+                let (reflected, transmitted) = reflection_weights(material, cos_i);
+                let bsdf = Bsdf::Phong {
+                    diffuse: diffuse_color * material.albedo[0],
+                    specular: material.albedo[1],
+                    exponent: material.specular,
+                };
+                (bsdf, reflected, transmitted)
+            }
+        };
+        let opaque = (1.0 - reflected - transmitted).max(0.0);
+
+        if opaque > 0.0 && !lights.is_empty() {
+            radiance += throughput * direct_light(&intersect, &normal, &view, &bsdf, scene, lights, rng) * opaque;
+        }
+
+        // Se sigue un solo lóbulo, elegido con la probabilidad de su peso, así el peso se cancela
+        let pick = rng.next_f32();
+        let next = if pick < opaque {
+            let Some(next) = bsdf.sample(&normal, &view, rng) else {
+                break;
+            };
+            let pdf = bsdf.pdf(&normal, &view, &next);
+            if pdf <= 0.0 {
+                break;
+            }
+            throughput = throughput * bsdf.evaluate(&normal, &view, &next) * (1.0 / pdf);
+            bsdf_pdf = Some(pdf);
+            next
+        } else if pick < opaque + reflected {
+            let microfacet = microfacet_normal(&normal, material.roughness, rng);
+            let glossy = reflect(&direction, &microfacet);
+            bsdf_pdf = None;
+            // Una microfaceta muy inclinada puede mandar el reflejo hacia adentro
+            if glossy.dot(&normal) > 0.0 { glossy } else { reflect(&direction, &normal) }.normalize()
+        } else {
+            let microfacet = microfacet_normal(&intersect.normal, material.roughness, rng);
+            let next = refract(&direction, &microfacet, material.refractive_index).normalize();
+            bsdf_pdf = None;
+
+            // Beer-Lambert: el volumen absorbe según lo que el camino recorre hasta salir
+            if cos_i < 0.0 && !material.absorption.is_black() {
+                if let Some(distance) = scene.exit_distance(&offset_origin(&intersect, &next), &next) {
+                    throughput = throughput * material.transmittance(distance);
+                }
+            }
+            next
+        };
+
+        // Ruleta rusa: los caminos que sobreviven compensan a los que se cortan
+        if bounce >= MIN_BOUNCES {
+            let survival = throughput.r.max(throughput.g).max(throughput.b).clamp(0.05, 0.95);
+            if rng.next_f32() >= survival {
+                break;
+            }
+            throughput = throughput * (1.0 / survival);
+        }
+
+        origin = offset_origin(&intersect, &next);
+        direction = next;
+    }
+
+    radiance
+}
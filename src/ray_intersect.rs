@@ -1,5 +1,65 @@
 use nalgebra_glm::Vec3;
 use crate::material::Material;
+use crate::bvh::Aabb;
+
+// Cara de una caja alineada a los ejes, en el orden -x, +x, -y, +y, -z, +z
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Left,
+    Right,
+    Bottom,
+    Top,
+    Back,
+    Front,
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [Face::Left, Face::Right, Face::Bottom, Face::Top, Face::Back, Face::Front];
+
+    // Cara perpendicular al eje dado (0 = x, 1 = y, 2 = z) del lado positivo o negativo
+    pub fn from_axis(axis: usize, positive: bool) -> Face {
+        Face::ALL[axis * 2 + positive as usize]
+    }
+
+    pub fn from_name(name: &str) -> Option<Face> {
+        Face::ALL.into_iter().find(|face| face.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Face::Left => "left",
+            Face::Right => "right",
+            Face::Bottom => "bottom",
+            Face::Top => "top",
+            Face::Back => "back",
+            Face::Front => "front",
+        }
+    }
+
+    // Posición en Face::ALL
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn axis(self) -> usize {
+        self.index() / 2
+    }
+
+    pub fn normal(self) -> Vec3 {
+        let mut normal = Vec3::new(0.0, 0.0, 0.0);
+        normal[self.axis()] = if self.index() % 2 == 1 { 1.0 } else { -1.0 };
+        normal
+    }
+
+    // Direcciones en que crecen u y v sobre la cara, según el mapeo UV de Cube y VoxelGrid
+    pub fn tangents(self) -> (Vec3, Vec3) {
+        match self.axis() {
+            0 => (Vec3::z(), Vec3::y()),
+            1 => (Vec3::x(), Vec3::z()),
+            _ => (Vec3::x(), Vec3::y()),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Intersect {
@@ -10,6 +70,11 @@ pub struct Intersect {
     pub material: Material,
     pub u: f32,
     pub v: f32,
+    // Cara golpeada, solo para cubos y bloques
+    pub face: Option<Face>,
+    // Unidades UV por unidad de distancia sobre la superficie; 0 si no se conoce.
+    // Con el ancho del cono del rayo da el tamaño del píxel en la textura (mipmaps).
+    pub uv_scale: f32,
 }
 
 impl Intersect {
@@ -22,9 +87,21 @@ impl Intersect {
             material,
             u,
             v,
+            face: None,
+            uv_scale: 0.0,
         }
     }
 
+    pub fn with_uv_scale(mut self, uv_scale: f32) -> Self {
+        self.uv_scale = uv_scale;
+        self
+    }
+
+    pub fn with_face(mut self, face: Face) -> Self {
+        self.face = Some(face);
+        self
+    }
+
     pub fn empty() -> Self {
         Intersect {
             point: Vec3::new(0.0, 0.0, 0.0),
@@ -34,12 +111,25 @@ impl Intersect {
             material: Material::black(),
             u: 0.0,
             v: 0.0,
+            face: None,
+            uv_scale: 0.0,
         }
     }
 }
 
 pub trait RayIntersect {
   fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
+  // Igual que ray_intersect, pero ignorando la geometría que no proyecta sombra
+  fn shadow_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+    self.ray_intersect(ray_origin, ray_direction)
+  }
+  // Distancia hasta salir del objeto para un rayo que nace dentro de él (p. ej. un rayo
+  // refractado); None si nace fuera o la forma no tiene volumen
+  fn exit_distance(&self, _ray_origin: &Vec3, _ray_direction: &Vec3) -> Option<f32> {
+    None
+  }
+  // Caja envolvente usada por la BVH de la escena
+  fn bounding_box(&self) -> Aabb;
 }
 
 
@@ -0,0 +1,187 @@
+use nalgebra_glm::{Vec3, normalize};
+use std::f32::consts::PI;
+use std::fmt;
+use rayon::prelude::*;
+
+use crate::accumulator::Accumulator;
+use crate::framebuffer::Framebuffer;
+use crate::castray::{cast_ray, RayCone};
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::light::Light;
+use crate::pathtrace::trace_path;
+use crate::sampling::{Rng, SamplePattern, PixelFilter};
+use crate::scene::Scene;
+
+// Campo de visión vertical de la cámara
+const FOV: f32 = PI / 3.0;
+
+// Separación entre semillas de pasadas progresivas consecutivas
+const FRAME_SEED_STRIDE: u64 = 0x9E37_79B9_7F4A_7C15;
+
+// Cómo se calcula la luz que trae cada rayo de la cámara
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Integrator {
+    // Trazado recursivo clásico: luz directa más reflejos y refracciones (cast_ray)
+    Whitted,
+    // Trazado de caminos Monte Carlo, con luz indirecta; necesita muchas muestras (trace_path)
+    PathTracer,
+}
+
+impl Integrator {
+    pub const ALL: [Integrator; 2] = [Integrator::Whitted, Integrator::PathTracer];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "whitted" => Some(Integrator::Whitted),
+            "path" => Some(Integrator::PathTracer),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Integrator::Whitted => "whitted",
+            Integrator::PathTracer => "path",
+        }
+    }
+
+    // Siguiente integrador, para alternarlos desde el teclado
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|integrator| *integrator == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for Integrator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSettings {
+    // Rayos por píxel; se redondea hacia arriba para llenar la cuadrícula del patrón
+    pub samples: u32,
+    pub pattern: SamplePattern,
+    pub filter: PixelFilter,
+    // Semilla del generador por píxel; el mismo valor reproduce el mismo render
+    pub seed: u64,
+    pub integrator: Integrator,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            samples: 1,
+            pattern: SamplePattern::Grid,
+            filter: PixelFilter::Box,
+            seed: 0,
+            integrator: Integrator::Whitted,
+        }
+    }
+}
+
+// Dirección del rayo primario que pasa por el punto (px, py) del plano de imagen, en píxeles
+fn primary_ray(camera: &Camera, px: f32, py: f32, width: usize, height: usize) -> Vec3 {
+    let aspect_ratio = width as f32 / height as f32;
+    let perspective_scale = (FOV * 0.5).tan();
+
+    let screen_x = (2.0 * px) / width as f32 - 1.0;
+    let screen_y = -(2.0 * py) / height as f32 + 1.0;
+
+    let screen_x = screen_x * aspect_ratio * perspective_scale;
+    let screen_y = screen_y * perspective_scale;
+
+    let ray_direction = normalize(&Vec3::new(screen_x, screen_y, -1.0));
+    camera.basis_change(&ray_direction)
+}
+
+// Apertura angular de una muestra: el alto de un píxel en el plano de imagen, repartido
+// entre las filas de submuestras
+fn sample_spread(height: usize, rows: u32) -> f32 {
+    2.0 * (FOV * 0.5).tan() / (height as f32 * rows as f32)
+}
+
+// Suma ponderada de las submuestras del píxel (x, y) y el peso total del filtro.
+// `frame` distingue pasadas progresivas: a partir de la segunda, todo el patrón se
+// desplaza aleatoriamente (rotación de Cranley-Patterson) para cubrir posiciones nuevas.
+fn sample_pixel(
+    (x, y): (usize, usize),
+    (width, height): (usize, usize),
+    frame: u32,
+    scene: &Scene,
+    camera: &Camera,
+    lights: &[Light],
+    settings: &RenderSettings,
+) -> (Color, f32) {
+    let seed = settings.seed.wrapping_add(frame as u64 * FRAME_SEED_STRIDE);
+    let mut rng = Rng::for_pixel(x, y, seed);
+    let grid = SamplePattern::grid_size(settings.samples);
+    let radius = settings.filter.radius();
+    let cone = RayCone::new(sample_spread(height, grid.1));
+    let shift = if frame == 0 { (0.0, 0.0) } else { (rng.next_f32(), rng.next_f32()) };
+
+    let mut sum = Color::black();
+    let mut weight_sum = 0.0;
+    for sample in 0..grid.0 * grid.1 {
+        // Las muestras cubren todo el soporte del filtro, centrado en el píxel
+        let (u, v) = settings.pattern.sample(sample, grid, &mut rng);
+        let (u, v) = ((u + shift.0).fract(), (v + shift.1).fract());
+        let (dx, dy) = ((2.0 * u - 1.0) * radius, (2.0 * v - 1.0) * radius);
+        let weight = settings.filter.weight(dx, dy);
+        if weight <= 0.0 {
+            continue;
+        }
+
+        let direction = primary_ray(camera, x as f32 + 0.5 + dx, y as f32 + 0.5 + dy, width, height);
+        let color = match settings.integrator {
+            Integrator::Whitted => cast_ray(&camera.eye, &direction, scene, lights, 0, cone, &mut rng),
+            Integrator::PathTracer => trace_path(&camera.eye, &direction, scene, lights, cone, &mut rng),
+        };
+        sum += color * weight;
+        weight_sum += weight;
+    }
+
+    (sum, weight_sum)
+}
+
+pub fn render(framebuffer: &mut Framebuffer, scene: &Scene, camera: &Camera, lights: &[Light], settings: &RenderSettings) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    let tone_mapper = framebuffer.tone_mapper;
+
+    framebuffer
+        .buffer
+        .par_iter_mut()
+        .enumerate()
+        .for_each(|(index, pixel)| {
+            let x = index % width;
+            let y = index / width;
+
+            // La radiancia se promedia en punto flotante y solo aquí pasa a 8 bits
+            let (sum, weight) = sample_pixel((x, y), (width, height), 0, scene, camera, lights, settings);
+            let pixel_color = if weight > 0.0 { sum * (1.0 / weight) } else { Color::black() };
+            *pixel = tone_mapper.to_hex(pixel_color);
+        });
+}
+
+// Agrega una pasada más al acumulador. La primera pasada es idéntica a `render`;
+// las siguientes usan posiciones de muestra nuevas, así la imagen converge mientras
+// la cámara y la escena no cambien.
+pub fn render_progressive(accumulator: &mut Accumulator, scene: &Scene, camera: &Camera, lights: &[Light], settings: &RenderSettings) {
+    let width = accumulator.width;
+    let height = accumulator.height;
+    let frame = accumulator.frames();
+
+    accumulator
+        .par_pixels_mut()
+        .enumerate()
+        .for_each(|(index, (radiance, weight))| {
+            let (sum, sample_weight) = sample_pixel((index % width, index / width), (width, height), frame, scene, camera, lights, settings);
+            *radiance += sum;
+            *weight += sample_weight;
+        });
+
+    accumulator.finish_frame();
+}
@@ -0,0 +1,172 @@
+use std::fmt;
+
+// Generador PCG32: pequeño, rápido y reproducible. Se siembra por píxel para que el
+// resultado no dependa del orden ni de la cantidad de hilos.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+}
+
+const PCG_MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+impl Rng {
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Rng {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    // Generador para un píxel concreto; `seed` distingue renders o cuadros distintos
+    pub fn for_pixel(x: usize, y: usize, seed: u64) -> Self {
+        let pixel = ((y as u64) << 32) | x as u64;
+        Rng::new(hash(pixel ^ seed.rotate_left(17)), hash(seed))
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    // Valor uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 * (1.0 / (1u32 << 24) as f32)
+    }
+}
+
+// Mezcla de bits (splitmix64) para derivar semillas independientes
+fn hash(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+// Distribución de las submuestras dentro de la huella del filtro.
+// Las muestras se acomodan en una cuadrícula de columnas x filas (ver `grid_size`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamplePattern {
+    // Cuadrícula regular fija (con una muestra, el centro del píxel)
+    Grid,
+    // Cuadrícula fija rotada, mejor para bordes casi horizontales o verticales
+    RotatedGrid,
+    // Un punto aleatorio dentro de cada celda de la cuadrícula
+    Stratified,
+}
+
+impl SamplePattern {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "grid" => Some(SamplePattern::Grid),
+            "rotated" => Some(SamplePattern::RotatedGrid),
+            "stratified" => Some(SamplePattern::Stratified),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SamplePattern::Grid => "grid",
+            SamplePattern::RotatedGrid => "rotated",
+            SamplePattern::Stratified => "stratified",
+        }
+    }
+
+    // Columnas y filas de la cuadrícula para al menos `count` muestras;
+    // el total real de muestras es columnas * filas
+    pub fn grid_size(count: u32) -> (u32, u32) {
+        let count = count.max(1);
+        let columns = (count as f32).sqrt().ceil() as u32;
+        (columns, count.div_ceil(columns))
+    }
+
+    // Posición de la muestra `index` en el cuadrado unitario para una cuadrícula dada
+    pub fn sample(self, index: u32, (columns, rows): (u32, u32), rng: &mut Rng) -> (f32, f32) {
+        let (cell_x, cell_y) = ((index % columns) as f32, (index / columns) as f32);
+        let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+
+        match self {
+            SamplePattern::Grid => ((cell_x + 0.5) * width, (cell_y + 0.5) * height),
+            SamplePattern::RotatedGrid => {
+                // Rotación de atan(1/2), la del patrón RGSS clásico de 4 muestras
+                let (x, y) = ((cell_x + 0.5) * width - 0.5, (cell_y + 0.5) * height - 0.5);
+                let (sin, cos) = 0.5f32.atan().sin_cos();
+                ((x * cos - y * sin + 0.5).rem_euclid(1.0), (x * sin + y * cos + 0.5).rem_euclid(1.0))
+            }
+            SamplePattern::Stratified => ((cell_x + rng.next_f32()) * width, (cell_y + rng.next_f32()) * height),
+        }
+    }
+}
+
+impl fmt::Display for SamplePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Filtro de reconstrucción que pondera cada muestra según su distancia al centro del píxel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFilter {
+    Box,
+    Tent,
+    Gaussian,
+}
+
+const GAUSSIAN_ALPHA: f32 = 2.0;
+
+impl PixelFilter {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "box" => Some(PixelFilter::Box),
+            "tent" => Some(PixelFilter::Tent),
+            "gaussian" => Some(PixelFilter::Gaussian),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PixelFilter::Box => "box",
+            PixelFilter::Tent => "tent",
+            PixelFilter::Gaussian => "gaussian",
+        }
+    }
+
+    // Radio del soporte del filtro, en píxeles
+    pub fn radius(self) -> f32 {
+        match self {
+            PixelFilter::Box => 0.5,
+            PixelFilter::Tent => 1.0,
+            PixelFilter::Gaussian => 1.5,
+        }
+    }
+
+    // Peso de una muestra desplazada (dx, dy) píxeles del centro
+    pub fn weight(self, dx: f32, dy: f32) -> f32 {
+        let radius = self.radius();
+        match self {
+            PixelFilter::Box => 1.0,
+            PixelFilter::Tent => (1.0 - dx.abs() / radius).max(0.0) * (1.0 - dy.abs() / radius).max(0.0),
+            PixelFilter::Gaussian => {
+                // Gaussiana truncada para que llegue a cero en el borde del soporte
+                let edge = (-GAUSSIAN_ALPHA * radius * radius).exp();
+                let gaussian = |d: f32| ((-GAUSSIAN_ALPHA * d * d).exp() - edge).max(0.0);
+                gaussian(dx) * gaussian(dy)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PixelFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
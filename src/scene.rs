@@ -0,0 +1,191 @@
+use nalgebra_glm::Vec3;
+use crate::ray_intersect::{RayIntersect, Intersect};
+use crate::bvh::Bvh;
+use crate::sky::Sky;
+
+// Avance tras cada impacto al seguir un rayo de sombra dentro del mismo objeto
+const MARCH_STEP: f32 = 1e-4;
+
+// Propiedades compartidas por cualquier objeto de la escena,
+// independientes de la forma geométrica
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectProperties {
+    pub casts_shadow: bool,
+    pub is_transparent: bool,
+}
+
+impl ObjectProperties {
+    pub const fn new(casts_shadow: bool, is_transparent: bool) -> Self {
+        ObjectProperties {
+            casts_shadow,
+            is_transparent,
+        }
+    }
+}
+
+impl Default for ObjectProperties {
+    fn default() -> Self {
+        ObjectProperties::new(true, false)
+    }
+}
+
+pub struct SceneObject {
+    pub shape: Box<dyn RayIntersect + Send + Sync>,
+    pub properties: ObjectProperties,
+}
+
+impl SceneObject {
+    pub fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.shape.ray_intersect(ray_origin, ray_direction)
+    }
+
+    pub fn shadow_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.shape.shadow_intersect(ray_origin, ray_direction)
+    }
+}
+
+// Contenedor de objetos heterogéneos (cubos, esferas, cuadrados...)
+#[derive(Default)]
+pub struct Scene {
+    pub objects: Vec<SceneObject>,
+    // Color de los rayos que no chocan con nada
+    pub sky: Sky,
+    // Se invalida al agregar objetos; sin BVH se prueban todos los objetos
+    bvh: Option<Bvh>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene {
+            objects: Vec::new(),
+            sky: Sky::default(),
+            bvh: None,
+        }
+    }
+
+    pub fn add<T>(&mut self, shape: T, properties: ObjectProperties)
+    where
+        T: RayIntersect + Send + Sync + 'static,
+    {
+        self.objects.push(SceneObject {
+            shape: Box::new(shape),
+            properties,
+        });
+        self.bvh = None;
+    }
+
+    // Construye la jerarquía de aceleración; llamar después de agregar todos los objetos
+    pub fn build_bvh(&mut self) {
+        let bounds: Vec<_> = self.objects.iter().map(|o| o.shape.bounding_box()).collect();
+        self.bvh = Some(Bvh::build(&bounds));
+    }
+
+    // Vuelve al recorrido lineal (útil para comparar rendimiento)
+    pub fn clear_bvh(&mut self) {
+        self.bvh = None;
+    }
+
+    pub fn has_bvh(&self) -> bool {
+        self.bvh.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    // Distancia hasta salir del objeto que contiene a `ray_origin`; si hay varios, el más cercano
+    pub fn exit_distance(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let mut exit: Option<f32> = None;
+        let mut test = |object: &SceneObject| {
+            if let Some(distance) = object.shape.exit_distance(ray_origin, ray_direction) {
+                exit = Some(exit.map_or(distance, |current| current.min(distance)));
+            }
+        };
+
+        match &self.bvh {
+            Some(bvh) => bvh.traverse(ray_origin, ray_direction, f32::INFINITY, |index| {
+                test(&self.objects[index]);
+                Some(f32::INFINITY)
+            }),
+            None => self.objects.iter().for_each(test),
+        }
+
+        exit
+    }
+
+    // Busca la intersección más cercana entre todos los objetos
+    pub fn intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        let mut intersect = Intersect::empty();
+        let mut zbuffer = f32::INFINITY;
+
+        let mut test = |object: &SceneObject| {
+            let i = object.ray_intersect(ray_origin, ray_direction);
+            if i.is_intersecting && i.distance < zbuffer {
+                zbuffer = i.distance;
+                intersect = i;
+            }
+            zbuffer
+        };
+
+        match &self.bvh {
+            Some(bvh) => bvh.traverse(ray_origin, ray_direction, f32::INFINITY, |index| {
+                Some(test(&self.objects[index]))
+            }),
+            None => {
+                for object in &self.objects {
+                    test(object);
+                }
+            }
+        }
+
+        intersect
+    }
+
+    // Visita cada superficie que proyecta sombra antes de `max_distance`, sin orden garantizado
+    // entre objetos. Dentro de un mismo objeto se sigue avanzando tras cada impacto, de modo que
+    // un mundo de bloques reporta todos los bloques atravesados.
+    // `visit` devuelve true para detener la búsqueda; el resultado indica si se detuvo.
+    pub fn any_hit<F>(&self, ray_origin: &Vec3, ray_direction: &Vec3, max_distance: f32, mut visit: F) -> bool
+    where
+        F: FnMut(&SceneObject, &Intersect) -> bool,
+    {
+        let mut stopped = false;
+
+        let mut test = |object: &SceneObject| {
+            let mut origin = *ray_origin;
+            let mut travelled = 0.0;
+            loop {
+                let mut i = object.shadow_intersect(&origin, ray_direction);
+                if !i.is_intersecting || travelled + i.distance >= max_distance {
+                    return true;
+                }
+                i.distance += travelled;
+                if visit(object, &i) {
+                    stopped = true;
+                    return false;
+                }
+                travelled = i.distance + MARCH_STEP;
+                origin = ray_origin + ray_direction * travelled;
+            }
+        };
+
+        match &self.bvh {
+            Some(bvh) => bvh.traverse(ray_origin, ray_direction, max_distance, |index| {
+                test(&self.objects[index]).then_some(max_distance)
+            }),
+            None => {
+                for object in &self.objects {
+                    if !test(object) {
+                        break;
+                    }
+                }
+            }
+        }
+
+        stopped
+    }
+}
@@ -0,0 +1,871 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use nalgebra_glm::Vec3;
+
+use crate::assets::{AssetManager, MissingAssets};
+use crate::atlas::Atlas;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::cube::{Cube, FaceMaterials};
+use crate::light::{Falloff, Light, SUN_ANGULAR_DIAMETER};
+use crate::material::Material;
+use crate::ray_intersect::Face;
+use crate::scene::{Scene, ObjectProperties};
+use crate::sky::DayCycle;
+use crate::sphere::Sphere;
+use crate::square::Square;
+use crate::texture::{ColorSpace, Texture, TextureError, TextureFilter, WrapMode};
+use crate::voxel::{VoxelGrid, BlockId, AIR};
+
+// Formato de escena: una directiva por línea, `#` inicia un comentario.
+//
+//   camera eye=5,5,5 center=0,0,0 up=0,1,0
+//   texture dirt ../assets/dirt.jpg            (ruta relativa al archivo de escena)
+//   texture sky ../assets/sky.png filter=bilinear wrap=clamp   (por defecto nearest, repeat)
+//   atlas blocks ../assets/blocks.png grid=16,16 tiles=blocks.tiles
+//   material stone texture=blocks tile=stone   (mosaico con nombre o `columna,fila`)
+//   material dirt texture=dirt specular=1 albedo=0.9,0.1,0,0 ior=0
+//   material red diffuse=255,0,0 specular=10 albedo=0.9,0.1,0,0
+//   material glass texture=glass cutout=0.5    (texeles con alfa menor a 0.5 son huecos)
+//   texture bumps ../assets/bumps.png space=linear   (datos, no color: mapas de normales)
+//   material rock texture=dirt normal=bumps normal_strength=0.5 specular=1 albedo=0.9,0.1,0,0
+//   material water texture=water specular=50 albedo=0.8,0.2,0.1,0.3 ior=1.33 tint=60,150,200 tint_distance=2
+//                                              (color que queda tras 2 unidades dentro del volumen)
+//   material frost diffuse=255,255,255 specular=50 albedo=0.1,0.2,0.1,0.7 ior=1.5 roughness=0.2
+//                                              (reflejos y refracciones borrosos)
+//   material lamp diffuse=255,240,200 specular=1 albedo=1,0,0,0 emission=255,240,200 emission_strength=2
+//                                              (las figuras y bloques emisivos se vuelven luces solos)
+//   material gold diffuse=255,200,80 metallic=1 roughness=0.3   (modelo físico, sin albedo)
+//   material tile texture=dirt roughness=1 roughness_map=rough ior=1.5 (mapas con space=linear)
+//   light position=1,4,10 color=255,255,255 intensity=1
+//   light position=0,1,0 range=3               (se apaga a 3 unidades; o falloff=inverse_square)
+//   light direction=-1,2,1 color=255,240,220 angle=0.53    (direccional; `angle` = diámetro en grados)
+//   light direction=-1,2,1 angle=2 samples=8   (varios rayos de sombra para una penumbra suave)
+//   area rect center=0,3,0 normal=0,-1,0 size=2,1 samples=16    (panel que ilumina hacia `normal`)
+//   area sphere center=0,2,0 radius=0.3 samples=8
+//   area cube min=0,0,0 max=1,1,1 material=lava samples=8   (con `material` también se ve el bloque)
+//   spot position=0,3,0 direction=0,-1,0 inner=20 outer=30 cookie=window range=6
+//                                              (foco: ángulos desde el eje, en grados; cookie = textura)
+//   sun time=17.5 intensity=1                  (sol, luna y cielo según la hora del día)
+//   cube min=0,0,0 max=1,1,1 material=red shadow=true transparent=false
+//   cube min=0,0,0 max=1,1,1 material=dirt top=grass sides=grass_side  (material por cara)
+//   sphere center=0,1,0 radius=0.5 material=red
+//   square center=0,0,0 normal=0,1,0 size=4 material=red
+//   world block_size=0.5                       (inicia un mundo de bloques)
+//   block dirt material=dirt shadow=true       (registra un tipo de bloque)
+//   set 0,0,0 dirt
+//   fill -3..3 0..1 -3..3 dirt                 (rangos semiabiertos; `air` borra)
+
+// Las figuras emisivas iluminan hasta EMITTER_RANGE veces su tamaño, con varios rayos de sombra
+const EMITTER_RANGE: f32 = 4.0;
+const EMITTER_SAMPLES: u32 = 4;
+
+// Claves opcionales de material por cara en `cube` y `block`
+const FACE_KEYS: &[&str] = &["sides", "top", "bottom", "left", "right", "back", "front"];
+
+// Claves que eligen el modelo metálico-rugoso en `material`
+const PBR_KEYS: &[&str] = &["metallic", "roughness", "metallic_map", "roughness_map"];
+
+pub struct LoadedScene {
+    pub scene: Scene,
+    pub lights: Vec<Light>,
+    pub camera: Camera,
+    // Texturas reemplazadas por el tablero de ajedrez (solo con LoadOptions::placeholders)
+    pub missing_assets: Vec<TextureError>,
+    // Ciclo de día de la directiva `sun` y la posición del sol en `lights` (la luna va después)
+    day_cycle: Option<(DayCycle, usize)>,
+}
+
+impl LoadedScene {
+    pub fn day_cycle(&self) -> Option<&DayCycle> {
+        self.day_cycle.as_ref().map(|(day_cycle, _)| day_cycle)
+    }
+
+    // Avanza el reloj del ciclo de día `hours` horas: mueve sol y luna y cambia el cielo.
+    // Devuelve false si la escena no tiene `sun`.
+    pub fn advance_day(&mut self, hours: f32) -> bool {
+        let Some((day_cycle, sun)) = self.day_cycle.as_mut() else {
+            return false;
+        };
+        day_cycle.advance(hours);
+        self.lights[*sun] = day_cycle.sun();
+        self.lights[*sun + 1] = day_cycle.moon();
+        self.scene.sky = day_cycle.sky();
+        true
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadOptions {
+    // Si una textura falta o está dañada, usar un tablero de ajedrez en vez de fallar
+    pub placeholders: bool,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { line: usize, message: String },
+    Assets(MissingAssets),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            SceneError::Parse { line, message } => write!(f, "línea {}: {}", line, message),
+            SceneError::Assets(missing) => write!(f, "{}", missing),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<LoadedScene, SceneError> {
+    load_scene_with(path, LoadOptions::default())
+}
+
+pub fn load_scene_with<P: AsRef<Path>>(path: P, options: LoadOptions) -> Result<LoadedScene, SceneError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|error| SceneError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    parse_scene_with(&source, base_dir, options)
+}
+
+// Directiva de una línea: nombre, argumentos posicionales y pares clave=valor
+struct Directive<'a> {
+    line: usize,
+    name: &'a str,
+    positional: Vec<&'a str>,
+    keys: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Directive<'a> {
+    fn parse(line: usize, text: &'a str) -> Result<Option<Self>, SceneError> {
+        let text = text.split('#').next().unwrap_or("").trim();
+        let mut tokens = text.split_whitespace();
+        let name = match tokens.next() {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+
+        let mut directive = Directive {
+            line,
+            name,
+            positional: Vec::new(),
+            keys: HashMap::new(),
+        };
+        for token in tokens {
+            match token.split_once('=') {
+                Some((key, value)) => {
+                    if directive.keys.insert(key, value).is_some() {
+                        return Err(directive.error(format!("clave `{}` repetida", key)));
+                    }
+                }
+                None => directive.positional.push(token),
+            }
+        }
+        Ok(Some(directive))
+    }
+
+    fn error(&self, message: String) -> SceneError {
+        SceneError::Parse { line: self.line, message }
+    }
+
+    // Verifica la cantidad de argumentos posicionales y que no haya claves desconocidas
+    fn expect(&self, positional: usize, allowed_keys: &[&str]) -> Result<(), SceneError> {
+        if self.positional.len() != positional {
+            return Err(self.error(format!(
+                "`{}` espera {} argumento(s) sin clave, se encontraron {}",
+                self.name,
+                positional,
+                self.positional.len()
+            )));
+        }
+        for key in self.keys.keys() {
+            if !allowed_keys.contains(key) {
+                return Err(self.error(format!("clave desconocida `{}` en `{}`", key, self.name)));
+            }
+        }
+        Ok(())
+    }
+
+    fn required(&self, key: &str) -> Result<&'a str, SceneError> {
+        self.keys
+            .get(key)
+            .copied()
+            .ok_or_else(|| self.error(format!("falta la clave `{}` en `{}`", key, self.name)))
+    }
+
+    fn f32_value(&self, key: &str, value: &str) -> Result<f32, SceneError> {
+        value
+            .parse::<f32>()
+            .map_err(|_| self.error(format!("`{}` no es un número válido para `{}`", value, key)))
+    }
+
+    fn floats<const N: usize>(&self, key: &str, value: &str) -> Result<[f32; N], SceneError> {
+        let parts: Vec<&str> = value.split(',').collect();
+        if parts.len() != N {
+            return Err(self.error(format!("`{}` espera {} valores separados por comas", key, N)));
+        }
+        let mut result = [0.0; N];
+        for (slot, part) in result.iter_mut().zip(parts) {
+            *slot = self.f32_value(key, part)?;
+        }
+        Ok(result)
+    }
+
+    fn get_f32(&self, key: &str) -> Result<f32, SceneError> {
+        self.f32_value(key, self.required(key)?)
+    }
+
+    fn get_f32_or(&self, key: &str, default: f32) -> Result<f32, SceneError> {
+        match self.keys.get(key) {
+            Some(value) => self.f32_value(key, value),
+            None => Ok(default),
+        }
+    }
+
+    fn get_vec3(&self, key: &str) -> Result<Vec3, SceneError> {
+        let [x, y, z] = self.floats::<3>(key, self.required(key)?)?;
+        Ok(Vec3::new(x, y, z))
+    }
+
+    fn get_albedo(&self) -> Result<[f32; 4], SceneError> {
+        self.floats::<4>("albedo", self.required("albedo")?)
+    }
+
+    fn color_value(&self, key: &str, value: &str) -> Result<Color, SceneError> {
+        let parts: Vec<&str> = value.split(',').collect();
+        if parts.len() != 3 {
+            return Err(self.error(format!("`{}` espera un color r,g,b", key)));
+        }
+        let mut rgb = [0u8; 3];
+        for (slot, part) in rgb.iter_mut().zip(parts) {
+            *slot = part
+                .parse::<u8>()
+                .map_err(|_| self.error(format!("`{}` no es un componente de color válido (0-255)", part)))?;
+        }
+        Ok(Color::from_srgb8(rgb[0], rgb[1], rgb[2]))
+    }
+
+    fn get_color(&self, key: &str) -> Result<Color, SceneError> {
+        self.color_value(key, self.required(key)?)
+    }
+
+    fn get_color_or(&self, key: &str, default: Color) -> Result<Color, SceneError> {
+        match self.keys.get(key) {
+            Some(value) => self.color_value(key, value),
+            None => Ok(default),
+        }
+    }
+
+    fn get_bool_or(&self, key: &str, default: bool) -> Result<bool, SceneError> {
+        match self.keys.get(key) {
+            Some(&"true") => Ok(true),
+            Some(&"false") => Ok(false),
+            Some(value) => Err(self.error(format!("`{}` debe ser true o false, no `{}`", key, value))),
+            None => Ok(default),
+        }
+    }
+
+    fn get_properties(&self) -> Result<ObjectProperties, SceneError> {
+        let defaults = ObjectProperties::default();
+        Ok(ObjectProperties::new(
+            self.get_bool_or("shadow", defaults.casts_shadow)?,
+            self.get_bool_or("transparent", defaults.is_transparent)?,
+        ))
+    }
+
+    // Emisión de un material sin textura: brilla con el color `emission`
+    fn plain_emission(&self, material: Material) -> Result<Material, SceneError> {
+        if !self.keys.contains_key("emission") && !self.keys.contains_key("emission_strength") {
+            return Ok(material);
+        }
+        if !self.keys.contains_key("emission") {
+            return Err(self.error("sin textura, la emisión necesita el color `emission`".to_string()));
+        }
+        Ok(material.with_emission(self.get_color("emission")?, self.get_f32_or("emission_strength", 1.0)?))
+    }
+
+    // Atenuación de las luces con posición: `range=R` o `falloff=none|inverse_square`
+    fn get_falloff(&self) -> Result<Falloff, SceneError> {
+        match (self.keys.get("falloff"), self.keys.contains_key("range")) {
+            (Some(_), true) => Err(self.error("`range` y `falloff` no se pueden combinar".to_string())),
+            (None, false) | (Some(&"none"), false) => Ok(Falloff::None),
+            (Some(&"inverse_square"), false) => Ok(Falloff::InverseSquare),
+            (Some(name), false) => Err(self.error(format!("atenuación `{}` desconocida (none, inverse_square)", name))),
+            (None, true) => {
+                let range = self.get_f32("range")?;
+                if range <= 0.0 {
+                    return Err(self.error("`range` debe ser positivo".to_string()));
+                }
+                Ok(Falloff::Range(range))
+            }
+        }
+    }
+
+    // Rayos de sombra por punto de una luz; por defecto uno
+    fn get_samples(&self) -> Result<u32, SceneError> {
+        match self.keys.get("samples") {
+            Some(value) => match self.int(value)? {
+                samples if samples >= 1 => Ok(samples as u32),
+                _ => Err(self.error("`samples` debe ser al menos 1".to_string())),
+            },
+            None => Ok(1),
+        }
+    }
+
+    fn block_coords(&self, value: &str) -> Result<[i32; 3], SceneError> {
+        let parts: Vec<&str> = value.split(',').collect();
+        if parts.len() != 3 {
+            return Err(self.error(format!("`{}` no es una coordenada de bloque x,y,z", value)));
+        }
+        let mut coords = [0; 3];
+        for (slot, part) in coords.iter_mut().zip(parts) {
+            *slot = self.int(part)?;
+        }
+        Ok(coords)
+    }
+
+    fn range(&self, value: &str) -> Result<(i32, i32), SceneError> {
+        let (start, end) = value
+            .split_once("..")
+            .ok_or_else(|| self.error(format!("`{}` no es un rango inicio..fin", value)))?;
+        Ok((self.int(start)?, self.int(end)?))
+    }
+
+    fn int(&self, value: &str) -> Result<i32, SceneError> {
+        value
+            .parse::<i32>()
+            .map_err(|_| self.error(format!("`{}` no es un entero válido", value)))
+    }
+}
+
+struct World {
+    grid: VoxelGrid,
+    blocks: HashMap<String, BlockId>,
+}
+
+#[derive(Default)]
+struct SceneBuilder {
+    scene: Scene,
+    lights: Vec<Light>,
+    camera: Option<Camera>,
+    assets: AssetManager,
+    textures: HashMap<String, Arc<Texture>>,
+    atlases: HashMap<String, Atlas>,
+    materials: HashMap<String, Material>,
+    worlds: Vec<World>,
+    day_cycle: Option<(DayCycle, usize)>,
+}
+
+impl SceneBuilder {
+    fn material(&self, directive: &Directive) -> Result<Material, SceneError> {
+        self.named_material(directive, directive.required("material")?)
+    }
+
+    fn named_material(&self, directive: &Directive, name: &str) -> Result<Material, SceneError> {
+        self.materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| directive.error(format!("material `{}` no definido", name)))
+    }
+
+    // `material` cubre todas las caras; `sides` las cuatro laterales y cada cara puede
+    // reemplazarse con su nombre (top, bottom, left, right, back, front)
+    fn face_materials(&self, directive: &Directive) -> Result<FaceMaterials, SceneError> {
+        let mut materials = FaceMaterials::uniform(self.material(directive)?);
+        if let Some(name) = directive.keys.get("sides") {
+            let sides = self.named_material(directive, name)?;
+            for face in [Face::Left, Face::Right, Face::Back, Face::Front] {
+                materials.set(face, sides.clone());
+            }
+        }
+        for face in Face::ALL {
+            if let Some(name) = directive.keys.get(face.name()) {
+                materials.set(face, self.named_material(directive, name)?);
+            }
+        }
+        Ok(materials)
+    }
+
+    // Material del modelo clásico: Phong con los cuatro pesos de `albedo`. Acepta `roughness`
+    // para reflejos y refracciones borrosos, pero no las demás claves físicas.
+    fn phong_material(&self, directive: &Directive, refractive_index: f32) -> Result<Material, SceneError> {
+        for key in ["metallic", "metallic_map", "roughness_map"] {
+            if directive.keys.contains_key(key) {
+                return Err(directive.error(format!("`{}` no aplica a materiales con albedo/specular", key)));
+            }
+        }
+        let specular = directive.get_f32("specular")?;
+        let albedo = directive.get_albedo()?;
+        let material = match directive.keys.get("texture") {
+            Some(texture_name) => {
+                let texture = self
+                    .textures
+                    .get(*texture_name)
+                    .cloned()
+                    .ok_or_else(|| directive.error(format!("textura `{}` no definida", texture_name)))?;
+                if directive.keys.contains_key("emission") || directive.keys.contains_key("emission_strength") {
+                    Material::new_with_emission_texture(
+                        specular,
+                        albedo,
+                        refractive_index,
+                        texture,
+                        directive.get_color_or("emission", Color::black())?,
+                        directive.get_f32_or("emission_strength", 1.0)?,
+                    )
+                } else {
+                    Material::new_with_texture(specular, albedo, refractive_index, texture)
+                }
+            }
+            None => {
+                let material = Material::new(directive.get_color("diffuse")?, specular, albedo, refractive_index);
+                directive.plain_emission(material)?
+            }
+        };
+        Ok(material.with_roughness(directive.get_f32_or("roughness", 0.0)?))
+    }
+
+    // Material metálico-rugoso
+    fn pbr_material(&self, directive: &Directive, refractive_index: f32) -> Result<Material, SceneError> {
+        let metallic = directive.get_f32_or("metallic", 0.0)?;
+        let roughness = directive.get_f32_or("roughness", 0.5)?;
+
+        let mut material = match directive.keys.get("texture") {
+            Some(texture_name) => {
+                let texture = self
+                    .textures
+                    .get(*texture_name)
+                    .cloned()
+                    .ok_or_else(|| directive.error(format!("textura `{}` no definida", texture_name)))?;
+                let material = Material::pbr_with_texture(texture, metallic, roughness, refractive_index);
+                if directive.keys.contains_key("emission") || directive.keys.contains_key("emission_strength") {
+                    material.with_emission(directive.get_color_or("emission", Color::black())?, directive.get_f32_or("emission_strength", 1.0)?)
+                } else {
+                    material
+                }
+            }
+            None => {
+                let material = Material::pbr(directive.get_color_or("diffuse", Color::white())?, metallic, roughness, refractive_index);
+                directive.plain_emission(material)?
+            }
+        };
+
+        if let Some(name) = directive.keys.get("metallic_map") {
+            material = material.with_metallic_map(self.data_texture(directive, name)?);
+        }
+        if let Some(name) = directive.keys.get("roughness_map") {
+            material = material.with_roughness_map(self.data_texture(directive, name)?);
+        }
+        Ok(material)
+    }
+
+    // Textura de datos (normales, metalicidad, rugosidad): debe cargarse sin decodificar sRGB
+    fn data_texture(&self, directive: &Directive, name: &str) -> Result<Arc<Texture>, SceneError> {
+        let texture = self
+            .textures
+            .get(name)
+            .cloned()
+            .ok_or_else(|| directive.error(format!("textura `{}` no definida", name)))?;
+        if texture.color_space != ColorSpace::Linear {
+            return Err(directive.error(format!("la textura de datos `{}` debe cargarse con space=linear", name)));
+        }
+        Ok(texture)
+    }
+
+    // Carga la imagen de `texture`/`atlas` y la registra con su nombre
+    fn texture(&mut self, directive: &Directive, base_dir: &Path) -> Result<Arc<Texture>, SceneError> {
+        let name = directive.positional[0];
+        let path = base_dir.join(directive.positional[1]);
+        let filter = match directive.keys.get("filter") {
+            Some(name) => TextureFilter::from_name(name)
+                .ok_or_else(|| directive.error(format!("filtro `{}` desconocido (nearest, bilinear)", name)))?,
+            None => TextureFilter::Nearest,
+        };
+        let wrap = match directive.keys.get("wrap") {
+            Some(name) => WrapMode::from_name(name)
+                .ok_or_else(|| directive.error(format!("modo `{}` desconocido (repeat, clamp, mirror)", name)))?,
+            None => WrapMode::Repeat,
+        };
+        let color_space = match directive.keys.get("space") {
+            Some(name) => ColorSpace::from_name(name)
+                .ok_or_else(|| directive.error(format!("espacio de color `{}` desconocido (srgb, linear)", name)))?,
+            None => ColorSpace::Srgb,
+        };
+        // Los errores de archivo se juntan en el AssetManager y se informan al final
+        let mut texture = self.assets.texture(&path, color_space);
+        if texture.filter != filter || texture.wrap != wrap {
+            texture = Arc::new(Texture::clone(&texture).with_sampling(filter, wrap));
+        }
+        if self.textures.insert(name.to_string(), texture.clone()).is_some() {
+            return Err(directive.error(format!("textura `{}` repetida", name)));
+        }
+        Ok(texture)
+    }
+
+    fn world(&mut self, directive: &Directive) -> Result<&mut World, SceneError> {
+        self.worlds
+            .last_mut()
+            .ok_or_else(|| directive.error(format!("`{}` requiere un `world` previo", directive.name)))
+    }
+
+    fn block_id(world: &World, directive: &Directive, name: &str) -> Result<BlockId, SceneError> {
+        if name == "air" {
+            return Ok(AIR);
+        }
+        world
+            .blocks
+            .get(name)
+            .copied()
+            .ok_or_else(|| directive.error(format!("bloque `{}` no definido", name)))
+    }
+
+    fn apply(&mut self, directive: &Directive, base_dir: &Path) -> Result<(), SceneError> {
+        match directive.name {
+            "camera" => {
+                directive.expect(0, &["eye", "center", "up"])?;
+                if self.camera.is_some() {
+                    return Err(directive.error("la cámara ya fue definida".to_string()));
+                }
+                self.camera = Some(Camera::new(
+                    directive.get_vec3("eye")?,
+                    directive.get_vec3("center")?,
+                    directive.get_vec3("up")?,
+                ));
+            }
+            "texture" => {
+                directive.expect(2, &["filter", "wrap", "space"])?;
+                self.texture(directive, base_dir)?;
+            }
+            "atlas" => {
+                directive.expect(2, &["grid", "tiles", "filter", "wrap", "space"])?;
+                let mut atlas = Atlas::new(self.texture(directive, base_dir)?);
+                if let Some(grid) = directive.keys.get("grid") {
+                    let (columns, rows) = grid
+                        .split_once(',')
+                        .ok_or_else(|| directive.error(format!("`{}` no es una cuadrícula columnas,filas", grid)))?;
+                    let (columns, rows) = (directive.int(columns)?.max(0), directive.int(rows)?.max(0));
+                    atlas.set_grid(columns as usize, rows as usize).map_err(|message| directive.error(message))?;
+                }
+                if let Some(tiles) = directive.keys.get("tiles") {
+                    let path = base_dir.join(tiles);
+                    atlas
+                        .load_tiles(&path)
+                        .map_err(|error| directive.error(format!("{}: {}", path.display(), error)))?;
+                }
+                self.atlases.insert(directive.positional[0].to_string(), atlas);
+            }
+            "material" => {
+                directive.expect(1, &["diffuse", "texture", "tile", "specular", "albedo", "ior", "emission", "emission_strength", "cutout", "normal", "normal_strength", "metallic", "roughness", "metallic_map", "roughness_map", "tint", "tint_distance"])?;
+                let name = directive.positional[0];
+                let refractive_index = directive.get_f32_or("ior", 0.0)?;
+
+                // `albedo` y `specular` son del modelo clásico; sin ellos, las claves físicas eligen el otro
+                let phong = directive.keys.contains_key("albedo") || directive.keys.contains_key("specular");
+                let mut material = if !phong && PBR_KEYS.iter().any(|key| directive.keys.contains_key(key)) {
+                    self.pbr_material(directive, refractive_index)?
+                } else {
+                    self.phong_material(directive, refractive_index)?
+                };
+
+                if let Some(tile_name) = directive.keys.get("tile") {
+                    let texture_name = directive.required("texture")?;
+                    let atlas = self
+                        .atlases
+                        .get(texture_name)
+                        .ok_or_else(|| directive.error(format!("`{}` no es un atlas", texture_name)))?;
+                    let tile = atlas
+                        .tile(tile_name)
+                        .ok_or_else(|| directive.error(format!("mosaico `{}` no definido en `{}`", tile_name, texture_name)))?;
+                    material = material.with_tile(tile);
+                }
+
+                if directive.keys.contains_key("cutout") {
+                    if !directive.keys.contains_key("texture") {
+                        return Err(directive.error("el recorte por alfa requiere una textura".to_string()));
+                    }
+                    material = material.with_alpha_cutout(directive.get_f32("cutout")?);
+                }
+
+                if let Some(normal_name) = directive.keys.get("normal") {
+                    // Decodificar un mapa de normales como sRGB torcería todas las normales
+                    let normal_map = self.data_texture(directive, normal_name)?;
+                    material = material.with_normal_map(normal_map, directive.get_f32_or("normal_strength", 1.0)?);
+                } else if directive.keys.contains_key("normal_strength") {
+                    return Err(directive.error("`normal_strength` requiere un mapa `normal`".to_string()));
+                }
+
+                if directive.keys.contains_key("tint") {
+                    let distance = directive.get_f32_or("tint_distance", 1.0)?;
+                    if distance <= 0.0 {
+                        return Err(directive.error("`tint_distance` debe ser positiva".to_string()));
+                    }
+                    material = material.with_tint(directive.get_color("tint")?, distance);
+                } else if directive.keys.contains_key("tint_distance") {
+                    return Err(directive.error("`tint_distance` requiere un color `tint`".to_string()));
+                }
+
+                if self.materials.insert(name.to_string(), material).is_some() {
+                    return Err(directive.error(format!("material `{}` repetido", name)));
+                }
+            }
+            "light" => {
+                directive.expect(0, &["position", "direction", "color", "intensity", "angle", "range", "falloff", "samples"])?;
+                let color = directive.get_color_or("color", Color::white())?;
+                let intensity = directive.get_f32_or("intensity", 1.0)?;
+                let light = match (directive.keys.contains_key("position"), directive.keys.contains_key("direction")) {
+                    (true, false) => {
+                        if directive.keys.contains_key("angle") {
+                            return Err(directive.error("`angle` solo aplica a luces con `direction`".to_string()));
+                        }
+                        Light::new(directive.get_vec3("position")?, color, intensity).with_falloff(directive.get_falloff()?)
+                    }
+                    (false, true) => {
+                        for key in ["range", "falloff"] {
+                            if directive.keys.contains_key(key) {
+                                return Err(directive.error(format!("`{}` no aplica a luces direccionales", key)));
+                            }
+                        }
+                        let angle = directive.get_f32_or("angle", SUN_ANGULAR_DIAMETER.to_degrees())?;
+                        Light::directional(directive.get_vec3("direction")?, color, intensity, angle.to_radians())
+                    }
+                    _ => return Err(directive.error("`light` lleva `position` (puntual) o `direction` (direccional)".to_string())),
+                };
+                self.lights.push(light.with_samples(directive.get_samples()?));
+            }
+            "area" => {
+                const LIGHT_KEYS: &[&str] = &["color", "intensity", "range", "falloff", "samples"];
+                let color = directive.get_color_or("color", Color::white())?;
+                let intensity = directive.get_f32_or("intensity", 1.0)?;
+                let light = match directive.positional.first().copied() {
+                    Some("rect") => {
+                        directive.expect(1, &[&["center", "normal", "size"][..], LIGHT_KEYS].concat())?;
+                        let [width, height] = directive.floats::<2>("size", directive.required("size")?)?;
+                        if width <= 0.0 || height <= 0.0 {
+                            return Err(directive.error("los lados de `size` deben ser positivos".to_string()));
+                        }
+                        let normal = directive.get_vec3("normal")?;
+                        if normal.magnitude() <= 1e-6 {
+                            return Err(directive.error("`normal` no puede ser el vector cero".to_string()));
+                        }
+                        Light::rect(directive.get_vec3("center")?, normal, width, height, color, intensity)
+                    }
+                    // Con `material` también se agrega la figura visible, sin sombra para no
+                    // tapar su propia luz
+                    Some("sphere") => {
+                        directive.expect(1, &[&["center", "radius", "material"][..], LIGHT_KEYS].concat())?;
+                        let (center, radius) = (directive.get_vec3("center")?, directive.get_f32("radius")?);
+                        if radius <= 0.0 {
+                            return Err(directive.error("`radius` debe ser positivo".to_string()));
+                        }
+                        if directive.keys.contains_key("material") {
+                            self.scene.add(Sphere::new(center, radius, self.material(directive)?), ObjectProperties::new(false, false));
+                        }
+                        Light::sphere(center, radius, color, intensity)
+                    }
+                    Some("cube") => {
+                        directive.expect(1, &[&["min", "max", "material"][..], LIGHT_KEYS].concat())?;
+                        let (min, max) = (directive.get_vec3("min")?, directive.get_vec3("max")?);
+                        if directive.keys.contains_key("material") {
+                            self.scene.add(Cube::new(min, max, self.material(directive)?), ObjectProperties::new(false, false));
+                        }
+                        Light::cube(min, max, color, intensity)
+                    }
+                    _ => return Err(directive.error("`area` espera la forma: rect, sphere o cube".to_string())),
+                };
+                self.lights.push(light.with_falloff(directive.get_falloff()?).with_samples(directive.get_samples()?));
+            }
+            "spot" => {
+                directive.expect(0, &["position", "direction", "color", "intensity", "inner", "outer", "range", "falloff", "cookie"])?;
+                let outer = directive.get_f32("outer")?;
+                let inner = directive.get_f32_or("inner", outer)?;
+                if !(0.0..90.0).contains(&outer) || !(0.0..=outer).contains(&inner) {
+                    return Err(directive.error("se espera 0 <= inner <= outer < 90 (grados)".to_string()));
+                }
+                let mut light = Light::spot(
+                    directive.get_vec3("position")?,
+                    directive.get_vec3("direction")?,
+                    directive.get_color_or("color", Color::white())?,
+                    directive.get_f32_or("intensity", 1.0)?,
+                    inner.to_radians(),
+                    outer.to_radians(),
+                )
+                .with_falloff(directive.get_falloff()?);
+                if let Some(name) = directive.keys.get("cookie") {
+                    let cookie = self
+                        .textures
+                        .get(*name)
+                        .cloned()
+                        .ok_or_else(|| directive.error(format!("textura `{}` no definida", name)))?;
+                    light = light.with_cookie(cookie);
+                }
+                self.lights.push(light);
+            }
+            "sun" => {
+                directive.expect(0, &["time", "intensity"])?;
+                if self.day_cycle.is_some() {
+                    return Err(directive.error("la escena ya tiene un `sun`".to_string()));
+                }
+                let mut day_cycle = DayCycle::new(directive.get_f32("time")?);
+                day_cycle.sun_intensity = directive.get_f32_or("intensity", 1.0)?;
+                // El sol y la luna ocupan dos lugares fijos en la lista de luces
+                self.day_cycle = Some((day_cycle, self.lights.len()));
+                self.lights.push(day_cycle.sun());
+                self.lights.push(day_cycle.moon());
+                self.scene.sky = day_cycle.sky();
+            }
+            "cube" => {
+                directive.expect(0, &[&["min", "max", "material", "shadow", "transparent"][..], FACE_KEYS].concat())?;
+                let (min, max) = (directive.get_vec3("min")?, directive.get_vec3("max")?);
+                let materials = self.face_materials(directive)?;
+                self.lights.extend(cube_emitters(min, max, &materials));
+                self.scene.add(Cube::with_face_materials(min, max, materials), directive.get_properties()?);
+            }
+            "sphere" => {
+                directive.expect(0, &["center", "radius", "material", "shadow", "transparent"])?;
+                let (center, radius, material) = (directive.get_vec3("center")?, directive.get_f32("radius")?, self.material(directive)?);
+                let light = emitter_light(material.average_emission(), 2.0 * radius, |color, intensity| Light::sphere(center, radius, color, intensity));
+                self.lights.extend(light);
+                self.scene.add(Sphere::new(center, radius, material), directive.get_properties()?);
+            }
+            "square" => {
+                directive.expect(0, &["center", "normal", "size", "material", "shadow", "transparent"])?;
+                let (center, normal, size) = (directive.get_vec3("center")?, directive.get_vec3("normal")?, directive.get_f32("size")?);
+                let material = self.material(directive)?;
+                let light = emitter_light(material.average_emission(), size, |color, intensity| Light::rect(center, normal, size, size, color, intensity));
+                self.lights.extend(light);
+                self.scene.add(Square::new(center, normal, size, material), directive.get_properties()?);
+            }
+            "world" => {
+                directive.expect(0, &["block_size"])?;
+                let block_size = directive.get_f32_or("block_size", 1.0)?;
+                if block_size <= 0.0 {
+                    return Err(directive.error("block_size debe ser positivo".to_string()));
+                }
+                self.worlds.push(World {
+                    grid: VoxelGrid::new(block_size),
+                    blocks: HashMap::new(),
+                });
+            }
+            "block" => {
+                // Todo el mundo es un solo objeto de la escena, así que solo `shadow` se aplica
+                // por bloque; la transparencia de las sombras sale del albedo del material
+                if directive.keys.contains_key("transparent") {
+                    return Err(directive.error(
+                        "los bloques no aceptan `transparent`; usa un material con albedo[3] > 0".to_string(),
+                    ));
+                }
+                directive.expect(1, &[&["material", "shadow"][..], FACE_KEYS].concat())?;
+                let name = directive.positional[0];
+                let materials = self.face_materials(directive)?;
+                let properties = directive.get_properties()?;
+                let world = self.world(directive)?;
+                if name == "air" || world.blocks.contains_key(name) {
+                    return Err(directive.error(format!("bloque `{}` repetido o reservado", name)));
+                }
+                let id = world.grid.register_block_faces(materials, properties);
+                world.blocks.insert(name.to_string(), id);
+            }
+            "set" => {
+                directive.expect(2, &[])?;
+                let [x, y, z] = directive.block_coords(directive.positional[0])?;
+                let world = self.world(directive)?;
+                let id = Self::block_id(world, directive, directive.positional[1])?;
+                world.grid.set(x, y, z, id);
+            }
+            "fill" => {
+                directive.expect(4, &[])?;
+                let (x0, x1) = directive.range(directive.positional[0])?;
+                let (y0, y1) = directive.range(directive.positional[1])?;
+                let (z0, z1) = directive.range(directive.positional[2])?;
+                let world = self.world(directive)?;
+                let id = Self::block_id(world, directive, directive.positional[3])?;
+                world.grid.fill([x0, y0, z0], [x1, y1, z1], id);
+            }
+            other => return Err(directive.error(format!("directiva desconocida `{}`", other))),
+        }
+        Ok(())
+    }
+}
+
+// Luz de área para una figura con material emisivo, del color de su emisión promedio; `shape`
+// arma la luz con la forma de la figura a partir del color y la intensidad
+fn emitter_light(emission: Color, size: f32, shape: impl FnOnce(Color, f32) -> Light) -> Option<Light> {
+    let intensity = emission.r.max(emission.g).max(emission.b);
+    if intensity <= 0.0 {
+        return None;
+    }
+    let light = shape(emission * (1.0 / intensity), intensity);
+    Some(light.with_falloff(Falloff::Range(EMITTER_RANGE * size)).with_samples(EMITTER_SAMPLES))
+}
+
+// Luces de un cubo emisivo: una caja si las seis caras emiten igual, si no un panel por cada
+// cara emisiva, así un bloque con solo la tapa encendida no ilumina hacia abajo
+fn cube_emitters(min: Vec3, max: Vec3, materials: &FaceMaterials) -> Vec<Light> {
+    let size = max - min;
+    let emission = Face::ALL.map(|face| materials.get(face).average_emission());
+    if emission.iter().all(|color| *color == emission[0]) {
+        return emitter_light(emission[0], size.max(), |color, intensity| Light::cube(min, max, color, intensity))
+            .into_iter()
+            .collect();
+    }
+
+    Face::ALL
+        .into_iter()
+        .filter_map(|face| {
+            emitter_light(emission[face.index()], size.max(), |color, intensity| {
+                Light::box_face(min, max, face, color, intensity)
+            })
+        })
+        .collect()
+}
+
+pub fn parse_scene(source: &str, base_dir: &Path) -> Result<LoadedScene, SceneError> {
+    parse_scene_with(source, base_dir, LoadOptions::default())
+}
+
+pub fn parse_scene_with(source: &str, base_dir: &Path, options: LoadOptions) -> Result<LoadedScene, SceneError> {
+    let mut builder = SceneBuilder {
+        assets: AssetManager::new(options.placeholders),
+        ..SceneBuilder::default()
+    };
+
+    for (index, text) in source.lines().enumerate() {
+        if let Some(directive) = Directive::parse(index + 1, text)? {
+            builder.apply(&directive, base_dir)?;
+        }
+    }
+
+    let missing_assets = builder.assets.finish().map_err(SceneError::Assets)?;
+    let camera = builder.camera.ok_or(SceneError::Parse {
+        line: source.lines().count(),
+        message: "la escena no define una cámara".to_string(),
+    })?;
+
+    let mut scene = builder.scene;
+    let mut lights = builder.lights;
+    for world in builder.worlds {
+        // El alcance depende del tamaño de un bloque, no del tamaño del rectángulo
+        let block_size = world.grid.block_size;
+        for (face, min, max, emission) in world.grid.emissive_faces() {
+            lights.extend(emitter_light(emission, block_size, |color, intensity| Light::box_face(min, max, face, color, intensity)));
+        }
+        scene.add(world.grid, ObjectProperties::default());
+    }
+    scene.build_bvh();
+
+    Ok(LoadedScene {
+        scene,
+        lights,
+        camera,
+        missing_assets,
+        day_cycle: builder.day_cycle,
+    })
+}
@@ -0,0 +1,116 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec3;
+
+use crate::color::Color;
+use crate::light::{Light, SUN_ANGULAR_DIAMETER};
+
+// Color del cielo según la altura de la dirección: `horizon` abajo, `zenith` arriba
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    pub zenith: Color,
+    pub horizon: Color,
+}
+
+impl Sky {
+    pub fn uniform(color: Color) -> Self {
+        Sky { zenith: color, horizon: color }
+    }
+
+    pub fn color(&self, direction: &Vec3) -> Color {
+        let t = direction.normalize().y.max(0.0);
+        self.horizon * (1.0 - t) + self.zenith * t
+    }
+}
+
+impl Default for Sky {
+    // El cielo amarillo pálido de siempre, escrito en sRGB como el resto de colores de la escena
+    fn default() -> Self {
+        Sky::uniform(Color::from_srgb8(253, 255, 146))
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+// Sol y luna que giran con la hora del día. El sol sale por +x a las 6, pasa por lo alto
+// a las 12 (inclinado hacia +z por la latitud) y se pone por -x a las 18; la luna va opuesta.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayCycle {
+    // Hora en [0, 24)
+    pub time: f32,
+    // Intensidad del sol al mediodía
+    pub sun_intensity: f32,
+    // Inclinación de la órbita respecto a la vertical, en radianes
+    pub tilt: f32,
+}
+
+impl DayCycle {
+    pub fn new(time: f32) -> Self {
+        DayCycle {
+            time: time.rem_euclid(24.0),
+            sun_intensity: 1.0,
+            tilt: 0.4,
+        }
+    }
+
+    // Avanza el reloj `hours` horas, dando la vuelta a medianoche
+    pub fn advance(&mut self, hours: f32) {
+        self.time = (self.time + hours).rem_euclid(24.0);
+    }
+
+    // Dirección hacia el sol
+    pub fn sun_direction(&self) -> Vec3 {
+        let angle = (self.time - 6.0) / 24.0 * 2.0 * PI;
+        let up = Vec3::new(0.0, self.tilt.cos(), self.tilt.sin());
+        (Vec3::x() * angle.cos() + up * angle.sin()).normalize()
+    }
+
+    // Altura del sol: 1 en el cenit, 0 en el horizonte, negativa de noche
+    pub fn sun_elevation(&self) -> f32 {
+        self.sun_direction().y
+    }
+
+    // Cálido cerca del horizonte, blanco a pleno día; se apaga al ponerse
+    pub fn sun(&self) -> Light {
+        let elevation = self.sun_elevation();
+        let color = mix(Color::from_srgb8(255, 140, 60), Color::from_srgb8(255, 244, 229), smoothstep(0.0, 0.4, elevation));
+        let intensity = self.sun_intensity * smoothstep(-0.05, 0.15, elevation);
+        Light::directional(self.sun_direction(), color, intensity, SUN_ANGULAR_DIAMETER)
+    }
+
+    // Luz azulada y tenue en el lado opuesto del sol
+    pub fn moon(&self) -> Light {
+        let intensity = 0.15 * self.sun_intensity * smoothstep(-0.05, 0.15, -self.sun_elevation());
+        Light::directional(-self.sun_direction(), Color::from_srgb8(150, 170, 255), intensity, SUN_ANGULAR_DIAMETER)
+    }
+
+    // Noche, amanecer/atardecer y día se mezclan según la altura del sol
+    pub fn sky(&self) -> Sky {
+        let elevation = self.sun_elevation();
+        let night = Sky {
+            zenith: Color::from_srgb8(5, 8, 20),
+            horizon: Color::from_srgb8(15, 20, 40),
+        };
+        let dusk = Sky {
+            zenith: Color::from_srgb8(90, 100, 170),
+            horizon: Color::from_srgb8(255, 150, 80),
+        };
+        let day = Sky {
+            zenith: Color::from_srgb8(110, 165, 255),
+            horizon: Color::from_srgb8(210, 225, 255),
+        };
+
+        let to_dusk = smoothstep(-0.2, 0.0, elevation);
+        let to_day = smoothstep(0.0, 0.35, elevation);
+        Sky {
+            zenith: mix(mix(night.zenith, dusk.zenith, to_dusk), day.zenith, to_day),
+            horizon: mix(mix(night.horizon, dusk.horizon, to_dusk), day.horizon, to_day),
+        }
+    }
+}
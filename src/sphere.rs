@@ -1,6 +1,7 @@
 use nalgebra_glm::{Vec3, dot};
 use crate::ray_intersect::{RayIntersect, Intersect};
 use crate::material::Material;
+use crate::bvh::Aabb;
 
 pub struct Sphere {
     pub center: Vec3,
@@ -9,6 +10,14 @@ pub struct Sphere {
 }
 
 impl Sphere {
+    pub fn new(center: Vec3, radius: f32, material: Material) -> Self {
+        Sphere {
+            center,
+            radius,
+            material,
+        }
+    }
+
     fn get_uv(&self, point: &Vec3) -> (f32, f32) {
         let normalized = (point - self.center) / self.radius;
         let u = 0.5 + (normalized.z.atan2(normalized.x) / (2.0 * std::f32::consts::PI));
@@ -16,20 +25,22 @@ impl Sphere {
         (u, v)
     }
 
-    fn perturb_normal(&self, normal: &Vec3, tangent_normal: &Vec3) -> Vec3 {
-        // Create a local coordinate system
-        let tangent = if normal.x.abs() > normal.y.abs() {
-            Vec3::new(-normal.z, 0.0, normal.x).normalize()
-        } else {
-            Vec3::new(0.0, -normal.z, normal.y).normalize()
-        };
-        let bitangent = normal.cross(&tangent);
+    // Intersección en `point` con la normal geométrica dada, perturbada por el mapa de normales
+    fn hit_at(&self, point: Vec3, geometric_normal: Vec3, distance: f32, u: f32, v: f32) -> Intersect {
+        let (tangent, bitangent) = self.tangents(&geometric_normal);
+        let normal = self.material.perturb_normal(&geometric_normal, &tangent, &bitangent, u, v);
 
-        // Transform the tangent normal to world space
-        let perturbed_normal = tangent * tangent_normal.x + bitangent * tangent_normal.y + normal * tangent_normal.z;
+        // u recorre el ecuador (2πr) y v un meridiano (πr)
+        let uv_scale = 1.0 / (std::f32::consts::PI * self.radius * std::f32::consts::SQRT_2);
+        Intersect::new(point, normal, distance, self.material.clone(), u, v).with_uv_scale(uv_scale)
+    }
 
-        let blend_factor = 10.0; // Adjust this value to control the strength of the normal map
-        (normal * (1.0 - blend_factor) + perturbed_normal * blend_factor).normalize()
+    // Direcciones en que crecen u (a lo largo del ecuador) y v (hacia el polo sur)
+    fn tangents(&self, normal: &Vec3) -> (Vec3, Vec3) {
+        let tangent = Vec3::new(-normal.z, 0.0, normal.x);
+        // En los polos u no está definida: cualquier tangente sirve
+        let tangent = if tangent.magnitude() > 1e-6 { tangent.normalize() } else { Vec3::x() };
+        (tangent, normal.cross(&tangent))
     }
 }
 
@@ -48,27 +59,54 @@ impl RayIntersect for Sphere {
 
         if discriminant > 0.0 {
             let t = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t_far = (-b + discriminant.sqrt()) / (2.0 * a);
             if t > 0.0 {
                 let point = ray_origin + ray_direction * t;
-                let geometric_normal = (point - self.center).normalize();
-                let distance = t; 
                 let (u, v) = self.get_uv(&point);
+                if !self.material.is_cutout_at(u, v) {
+                    return self.hit_at(point, (point - self.center).normalize(), t, u, v);
+                }
 
-                let normal = if self.material.has_normal_map {
-                    let tangent_normal = self.material.get_normal_from_map(u, v);
-                    self.perturb_normal(&geometric_normal, &tangent_normal)
-                } else {
-                    geometric_normal
-                };
+                // Hueco recortado por alfa: el rayo sigue hasta el lado opuesto, visto desde adentro
+                let point = ray_origin + ray_direction * t_far;
+                let (u, v) = self.get_uv(&point);
+                if self.material.is_cutout_at(u, v) {
+                    return Intersect::empty();
+                }
+                return self.hit_at(point, (self.center - point).normalize(), t_far, u, v);
+            }
 
-                return Intersect::new(point, normal, distance, self.material.clone(), u, v);
+            // Un rayo que nace adentro (refractado) sale de la esfera maciza por el lado opuesto.
+            // Conserva la normal exterior: con ella `cos_i > 0` indica la salida.
+            if t_far > 0.0 {
+                let point = ray_origin + ray_direction * t_far;
+                let (u, v) = self.get_uv(&point);
+                if self.material.is_cutout_at(u, v) {
+                    return Intersect::empty();
+                }
+                return self.hit_at(point, (point - self.center).normalize(), t_far, u, v);
             }
         }
 
         // If no intersection, return an empty intersect
         Intersect::empty()
     }
-}
-
 
+    fn exit_distance(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let oc = ray_origin - self.center;
+        if dot(&oc, &oc) > self.radius * self.radius {
+            return None;
+        }
+        // Desde adentro la raíz mayor siempre es positiva
+        let a = dot(ray_direction, ray_direction);
+        let b = 2.0 * dot(&oc, ray_direction);
+        let c = dot(&oc, &oc) - self.radius * self.radius;
+        let discriminant = (b * b - 4.0 * a * c).max(0.0);
+        Some((-b + discriminant.sqrt()) / (2.0 * a))
+    }
 
+    fn bounding_box(&self) -> Aabb {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Aabb::new(self.center - r, self.center + r)
+    }
+}
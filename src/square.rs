@@ -1,6 +1,7 @@
-use nalgebra_glm::{Vec3, dot, cross};
+use nalgebra_glm::Vec3;
 use crate::ray_intersect::{RayIntersect, Intersect};
 use crate::material::Material;
+use crate::bvh::Aabb;
 
 pub struct Square {
     pub center: Vec3,       // Centro del cuadrado
@@ -10,7 +11,30 @@ pub struct Square {
 }
 
 impl Square {
-    pub fn ray_intersect(&self, origin: &Vec3, direction: &Vec3) -> Intersect {
+    pub fn new(center: Vec3, normal: Vec3, size: f32, material: Material) -> Self {
+        Square {
+            center,
+            normal: normal.normalize(),
+            size,
+            material,
+        }
+    }
+
+    // Ejes del plano del cuadrado; para normales en Z coinciden con X e Y
+    fn axes(&self) -> (Vec3, Vec3) {
+        let reference = if self.normal.y.abs() < 0.999 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(0.0, 0.0, 1.0)
+        };
+        let tangent = reference.cross(&self.normal).normalize();
+        let bitangent = self.normal.cross(&tangent);
+        (tangent, bitangent)
+    }
+}
+
+impl RayIntersect for Square {
+    fn ray_intersect(&self, origin: &Vec3, direction: &Vec3) -> Intersect {
         let denom = self.normal.dot(direction);
         
         // Si el rayo es paralelo al plano del cuadrado, no hay intersección
@@ -30,19 +54,34 @@ impl Square {
         // Chequea si el punto de intersección está dentro de los límites del cuadrado
         let local_hit_point = hit_point - self.center;
         let half_size = self.size / 2.0;
+        let (tangent, bitangent) = self.axes();
+        let local_x = local_hit_point.dot(&tangent);
+        let local_y = local_hit_point.dot(&bitangent);
+
+        let (u, v) = (local_x / self.size + 0.5, local_y / self.size + 0.5);
 
-        if local_hit_point.x.abs() <= half_size && local_hit_point.y.abs() <= half_size {
+        // Los huecos recortados por alfa no existen para ningún rayo
+        if local_x.abs() <= half_size && local_y.abs() <= half_size && !self.material.is_cutout_at(u, v) {
             Intersect {
                 point: hit_point,
-                normal: self.normal,
+                normal: self.material.perturb_normal(&self.normal, &tangent, &bitangent, u, v),
                 distance: d,
                 is_intersecting: true,
                 material: self.material.clone(),
-                u: 0.0,  // coordenadas UV para texturizado
-                v: 0.0,
+                u,  // coordenadas UV para texturizado
+                v,
+                face: None,
+                uv_scale: 1.0 / self.size,
             }
         } else {
             Intersect::empty()  // No está dentro de los límites del cuadrado
         }
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let (tangent, bitangent) = self.axes();
+        let half_size = self.size / 2.0;
+        let extent = (tangent.abs() + bitangent.abs()) * half_size + Vec3::new(1e-4, 1e-4, 1e-4);
+        Aabb::new(self.center - extent, self.center + extent)
+    }
 }
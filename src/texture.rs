@@ -1,39 +1,313 @@
 extern crate image;
-use image::{ImageReader, Pixel, DynamicImage, GenericImageView};
+use image::{ImageReader, ImageError, Pixel, DynamicImage, GenericImageView, Rgb, RgbImage};
 use std::fmt;
+use std::io;
+use std::ops::{Add, Mul};
+use std::path::{Path, PathBuf};
+use crate::atlas::Tile;
 use crate::color::Color;
 
+// Cómo interpretar los valores de 8 bits de la imagen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+  // Colores de albedo/emisión: se decodifican de sRGB a lineal al cargar
+  Srgb,
+  // Datos (p. ej. mapas de normales): se usan tal cual, divididos entre 255
+  Linear,
+}
+
+impl ColorSpace {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "srgb" => Some(ColorSpace::Srgb),
+      "linear" => Some(ColorSpace::Linear),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      ColorSpace::Srgb => "srgb",
+      ColorSpace::Linear => "linear",
+    }
+  }
+}
+
+// Cómo se combina la textura entre texeles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureFilter {
+  // El texel más cercano: bordes duros, estilo pixel art
+  Nearest,
+  // Interpolación entre los cuatro texeles vecinos
+  Bilinear,
+}
+
+impl TextureFilter {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "nearest" => Some(TextureFilter::Nearest),
+      "bilinear" => Some(TextureFilter::Bilinear),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      TextureFilter::Nearest => "nearest",
+      TextureFilter::Bilinear => "bilinear",
+    }
+  }
+}
+
+// Qué pasa con las coordenadas UV fuera de [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+  Repeat,
+  Clamp,
+  Mirror,
+}
+
+impl WrapMode {
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "repeat" => Some(WrapMode::Repeat),
+      "clamp" => Some(WrapMode::Clamp),
+      "mirror" => Some(WrapMode::Mirror),
+      _ => None,
+    }
+  }
+
+  pub fn name(self) -> &'static str {
+    match self {
+      WrapMode::Repeat => "repeat",
+      WrapMode::Clamp => "clamp",
+      WrapMode::Mirror => "mirror",
+    }
+  }
+
+  // Lleva un índice de texel cualquiera al rango [0, size)
+  fn apply(self, index: i64, size: usize) -> usize {
+    let size = size as i64;
+    let wrapped = match self {
+      WrapMode::Repeat => index.rem_euclid(size),
+      WrapMode::Clamp => index.clamp(0, size - 1),
+      WrapMode::Mirror => {
+        let period = index.rem_euclid(2 * size);
+        if period >= size { 2 * size - 1 - period } else { period }
+      }
+    };
+    wrapped as usize
+  }
+}
+
+// Error al cargar una textura; siempre incluye la ruta del archivo
+#[derive(Debug)]
+pub enum TextureError {
+  NotFound { path: PathBuf },
+  Io { path: PathBuf, error: io::Error },
+  UnsupportedFormat { path: PathBuf, message: String },
+  Decode { path: PathBuf, message: String },
+}
+
+impl TextureError {
+  pub fn path(&self) -> &Path {
+    match self {
+      TextureError::NotFound { path }
+      | TextureError::Io { path, .. }
+      | TextureError::UnsupportedFormat { path, .. }
+      | TextureError::Decode { path, .. } => path,
+    }
+  }
+}
+
+impl fmt::Display for TextureError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      TextureError::NotFound { path } => write!(f, "{}: no se encontró el archivo", path.display()),
+      TextureError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+      TextureError::UnsupportedFormat { path, message } => {
+        write!(f, "{}: formato de imagen no soportado ({})", path.display(), message)
+      }
+      TextureError::Decode { path, message } => write!(f, "{}: no se pudo decodificar ({})", path.display(), message),
+    }
+  }
+}
+
+impl std::error::Error for TextureError {}
+
+// Lado del tablero de ajedrez que reemplaza a las texturas que no cargan, y de cada casilla
+const PLACEHOLDER_SIZE: u32 = 64;
+const PLACEHOLDER_CELL: u32 = 8;
+
+// Un nivel de la cadena de mipmaps; el 0 es la imagen original
+#[derive(Clone)]
+struct MipLevel {
+  width: usize,
+  height: usize,
+  texels: Vec<Color>,
+  // Opacidad de cada texel (1.0 si la imagen no tiene canal alfa)
+  alpha: Vec<f32>,
+}
+
+impl MipLevel {
+  // Siguiente nivel: cada texel promedia un bloque de 2x2 (en espacio lineal)
+  fn downsample(&self) -> MipLevel {
+    let width = (self.width / 2).max(1);
+    let height = (self.height / 2).max(1);
+    let mut texels = Vec::with_capacity(width * height);
+    let mut alpha = Vec::with_capacity(width * height);
+    for y in 0..height {
+      for x in 0..width {
+        let mut sum = Color::black();
+        let mut alpha_sum = 0.0;
+        for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+          let sx = (2 * x + dx).min(self.width - 1);
+          let sy = (2 * y + dy).min(self.height - 1);
+          sum += self.texels[sy * self.width + sx];
+          alpha_sum += self.alpha[sy * self.width + sx];
+        }
+        texels.push(sum * 0.25);
+        alpha.push(alpha_sum * 0.25);
+      }
+    }
+    MipLevel { width, height, texels, alpha }
+  }
+}
+
 #[derive(Clone)]
 pub struct Texture {
   image: DynamicImage,
   pub width: usize,
   pub height: usize,
-  color_array: Vec<Color>,
+  pub color_space: ColorSpace,
+  pub filter: TextureFilter,
+  pub wrap: WrapMode,
+  // La imagen trae canal alfa (p. ej. PNG RGBA)
+  pub has_alpha: bool,
+  levels: Vec<MipLevel>,
+  placeholder: bool,
 }
 
 impl Texture {
   pub fn new(file_path: &str) -> Texture {
-    let img = ImageReader::open(file_path).unwrap().decode().unwrap();
+    Texture::with_color_space(file_path, ColorSpace::Srgb)
+  }
+
+  pub fn new_linear(file_path: &str) -> Texture {
+    Texture::with_color_space(file_path, ColorSpace::Linear)
+  }
+
+  // Igual que load_with_color_space, pero un archivo faltante o dañado detiene el programa
+  pub fn with_color_space(file_path: &str, color_space: ColorSpace) -> Texture {
+    Texture::load_with_color_space(file_path, color_space).unwrap_or_else(|error| panic!("{}", error))
+  }
+
+  pub fn load<P: AsRef<Path>>(path: P) -> Result<Texture, TextureError> {
+    Texture::load_with_color_space(path, ColorSpace::Srgb)
+  }
+
+  pub fn load_with_color_space<P: AsRef<Path>>(path: P, color_space: ColorSpace) -> Result<Texture, TextureError> {
+    let path = path.as_ref();
+    let io_error = |error: io::Error| match error.kind() {
+      io::ErrorKind::NotFound => TextureError::NotFound { path: path.to_path_buf() },
+      _ => TextureError::Io { path: path.to_path_buf(), error },
+    };
+
+    // El formato se deduce del contenido, no solo de la extensión
+    let reader = ImageReader::open(path).map_err(io_error)?.with_guessed_format().map_err(io_error)?;
+    if reader.format().is_none() {
+      return Err(TextureError::UnsupportedFormat {
+        path: path.to_path_buf(),
+        message: "formato desconocido".to_string(),
+      });
+    }
+    let image = reader.decode().map_err(|error| match error {
+      ImageError::IoError(error) => io_error(error),
+      ImageError::Unsupported(error) => TextureError::UnsupportedFormat {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+      },
+      other => TextureError::Decode {
+        path: path.to_path_buf(),
+        message: other.to_string(),
+      },
+    })?;
+    Ok(Texture::from_image(image, color_space))
+  }
+
+  // Tablero de ajedrez magenta y negro que ocupa el lugar de una textura que no cargó
+  pub fn checkerboard() -> Texture {
+    let image = RgbImage::from_fn(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, |x, y| {
+      if (x / PLACEHOLDER_CELL + y / PLACEHOLDER_CELL).is_multiple_of(2) {
+        Rgb([255, 0, 255])
+      } else {
+        Rgb([0, 0, 0])
+      }
+    });
+    let mut texture = Texture::from_image(DynamicImage::ImageRgb8(image), ColorSpace::Srgb);
+    texture.placeholder = true;
+    texture
+  }
+
+  pub fn is_placeholder(&self) -> bool {
+    self.placeholder
+  }
+
+  fn from_image(img: DynamicImage, color_space: ColorSpace) -> Texture {
     let width = img.width() as usize;
     let height = img.height() as usize;
+    let has_alpha = img.color().has_alpha();
     let mut texture = Texture {
       image: img,
       width,
       height,
-      color_array: vec![Color::black(); width * height],
+      color_space,
+      filter: TextureFilter::Nearest,
+      wrap: WrapMode::Repeat,
+      has_alpha,
+      levels: Vec::new(),
+      placeholder: false,
     };
     texture.load_color_array();
+    texture.build_mip_chain();
     texture
   }
 
   fn load_color_array(&mut self) {
+    let mut color_array = vec![Color::black(); self.width * self.height];
+    let mut alpha = vec![1.0; self.width * self.height];
     for x in 0..self.width {
       for y in 0..self.height {
-        let pixel = self.image.get_pixel(x as u32, y as u32).to_rgb();
-        let color = ((pixel[0] as u32) << 16) | ((pixel[1] as u32) << 8) | (pixel[2] as u32);
-        self.color_array[y * self.width + x] = Color::from_hex(color);
+        let pixel = self.image.get_pixel(x as u32, y as u32).to_rgba();
+        color_array[y * self.width + x] = match self.color_space {
+          ColorSpace::Srgb => Color::from_srgb8(pixel[0], pixel[1], pixel[2]),
+          ColorSpace::Linear => Color::from_rgb8(pixel[0], pixel[1], pixel[2]),
+        };
+        // El alfa siempre es lineal, aunque el color venga en sRGB
+        alpha[y * self.width + x] = pixel[3] as f32 / 255.0;
       }
     }
+    self.levels = vec![MipLevel {
+      width: self.width,
+      height: self.height,
+      texels: color_array,
+      alpha,
+    }];
+  }
+
+  // Reduce la imagen a la mitad hasta llegar a 1x1
+  fn build_mip_chain(&mut self) {
+    let mut last = &self.levels[0];
+    let mut chain = Vec::new();
+    while last.width > 1 || last.height > 1 {
+      chain.push(last.downsample());
+      last = chain.last().unwrap();
+    }
+    self.levels.extend(chain);
+  }
+
+  pub fn mip_levels(&self) -> usize {
+    self.levels.len()
   }
 
   /*
@@ -41,16 +315,121 @@ impl Texture {
     if x >= self.width || y >= self.height {
       0xFF00FF
     } else {
-      self.color_array[y * self.width + x].to_hex()
+      self.levels[0].texels[y * self.width + x].to_hex()
     }
   }
   */
 
+  pub fn with_sampling(mut self, filter: TextureFilter, wrap: WrapMode) -> Texture {
+    self.filter = filter;
+    self.wrap = wrap;
+    self
+  }
+
+  // Color en las coordenadas UV (v = 1 es el borde superior de la imagen)
+  pub fn sample(&self, u: f32, v: f32) -> Color {
+    self.sample_tile(u, v, &Tile::full(self))
+  }
+
+  // Igual que sample, pero dentro de un mosaico: el ajuste de bordes y el filtro
+  // trabajan sobre el rectángulo, así los mosaicos vecinos de un atlas no se mezclan
+  pub fn sample_tile(&self, u: f32, v: f32, tile: &Tile) -> Color {
+    self.sample_level(u, v, tile, 0)
+  }
+
+  // Muestra con mipmaps. `footprint` es el ancho en UV que cubre el píxel sobre la
+  // superficie; con 0 se usa la imagen original. Con filtro bilineal se interpola
+  // además entre los dos niveles más cercanos (trilineal).
+  pub fn sample_filtered(&self, u: f32, v: f32, tile: &Tile, footprint: f32) -> Color {
+    let lod = (footprint * tile.width.max(tile.height) as f32).log2();
+    if lod.is_nan() || lod <= 0.0 {
+      return self.sample_level(u, v, tile, 0);
+    }
+    let lod = lod.min(self.max_level(tile) as f32);
+
+    match self.filter {
+      TextureFilter::Nearest => self.sample_level(u, v, tile, lod.round() as usize),
+      TextureFilter::Bilinear => {
+        let level = lod.floor() as usize;
+        let t = lod - level as f32;
+        let fine = self.sample_level(u, v, tile, level);
+        if t <= 0.0 {
+          return fine;
+        }
+        fine * (1.0 - t) + self.sample_level(u, v, tile, level + 1) * t
+      }
+    }
+  }
+
+  // Nivel más reducido en el que el mosaico sigue alineado a texeles enteros
+  fn max_level(&self, tile: &Tile) -> usize {
+    (1..self.levels.len())
+      .take_while(|&level| self.tile_at_level(tile, level).is_some())
+      .last()
+      .unwrap_or(0)
+  }
+
+  fn tile_at_level(&self, tile: &Tile, level: usize) -> Option<Tile> {
+    let mip = &self.levels[level];
+    let scale = |value: usize, full: usize, reduced: usize| {
+      let scaled = value * reduced;
+      scaled.is_multiple_of(full).then_some(scaled / full)
+    };
+    let tile = Tile::new(
+      scale(tile.x, self.width, mip.width)?,
+      scale(tile.y, self.height, mip.height)?,
+      scale(tile.width, self.width, mip.width)?,
+      scale(tile.height, self.height, mip.height)?,
+    );
+    (tile.width > 0 && tile.height > 0).then_some(tile)
+  }
+
+  fn sample_level(&self, u: f32, v: f32, tile: &Tile, level: usize) -> Color {
+    self.filter_level(u, v, tile, level, |mip, index| mip.texels[index])
+  }
+
+  // Opacidad en (u, v) a resolución completa, con el mismo filtro y ajuste que el color
+  pub fn sample_alpha(&self, u: f32, v: f32, tile: &Tile) -> f32 {
+    self.filter_level(u, v, tile, 0, |mip, index| mip.alpha[index])
+  }
+
+  // Filtra cualquier canal de un nivel; `fetch` lee el valor del texel con ese índice
+  fn filter_level<T, F>(&self, u: f32, v: f32, tile: &Tile, level: usize, fetch: F) -> T
+  where
+    T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+    F: Fn(&MipLevel, usize) -> T,
+  {
+    let mip = &self.levels[level];
+    let tile = if level == 0 { *tile } else { self.tile_at_level(tile, level).unwrap() };
+    let x = u * tile.width as f32;
+    let y = (1.0 - v) * tile.height as f32;
+    let texel = |tx: i64, ty: i64| {
+      let tx = tile.x + self.wrap.apply(tx, tile.width);
+      let ty = tile.y + self.wrap.apply(ty, tile.height);
+      fetch(mip, ty * mip.width + tx)
+    };
+
+    match self.filter {
+      TextureFilter::Nearest => texel(x.floor() as i64, y.floor() as i64),
+      TextureFilter::Bilinear => {
+        // Centros de texel en medios enteros
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1, y0) * fx;
+        let bottom = texel(x0, y0 + 1) * (1.0 - fx) + texel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+      }
+    }
+  }
+
   pub fn get_color(&self, x: usize, y: usize) -> Color {
     if x >= self.width || y >= self.height {
       Color::from_hex(0xFF00FF)
     } else {
-      self.color_array[y * self.width + x]
+      self.levels[0].texels[y * self.width + x]
     }
   }
 }
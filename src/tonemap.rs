@@ -0,0 +1,100 @@
+use std::fmt;
+use crate::color::Color;
+
+// Curva usada para comprimir la radiancia HDR al rango 0-1 antes de la codificación sRGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapOperator {
+    Clamp,
+    Reinhard,
+    AcesFilmic,
+}
+
+impl ToneMapOperator {
+    pub const ALL: [ToneMapOperator; 3] = [
+        ToneMapOperator::Clamp,
+        ToneMapOperator::Reinhard,
+        ToneMapOperator::AcesFilmic,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "clamp" => Some(ToneMapOperator::Clamp),
+            "reinhard" => Some(ToneMapOperator::Reinhard),
+            "aces" => Some(ToneMapOperator::AcesFilmic),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMapOperator::Clamp => "clamp",
+            ToneMapOperator::Reinhard => "reinhard",
+            ToneMapOperator::AcesFilmic => "aces",
+        }
+    }
+
+    // Siguiente operador, para alternarlos desde el teclado
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|op| *op == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn apply(self, color: Color) -> Color {
+        match self {
+            ToneMapOperator::Clamp => color,
+            ToneMapOperator::Reinhard => {
+                // Sobre la luminancia, para no desaturar los colores
+                let luminance = color.luminance();
+                if luminance <= 0.0 {
+                    return Color::black();
+                }
+                color * (1.0 / (1.0 + luminance))
+            }
+            ToneMapOperator::AcesFilmic => Color::new(aces(color.r), aces(color.g), aces(color.b)),
+        }
+    }
+}
+
+impl fmt::Display for ToneMapOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Aproximación de Narkowicz de la curva filmica ACES
+fn aces(x: f32) -> f32 {
+    let x = x.max(0.0);
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+// Convierte radiancia lineal en píxeles de 8 bits: exposición, curva y gamma sRGB
+#[derive(Debug, Clone, Copy)]
+pub struct ToneMapper {
+    pub operator: ToneMapOperator,
+    // Exposición en pasos (EV): cada paso duplica la luz
+    pub exposure: f32,
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        ToneMapper {
+            operator: ToneMapOperator::AcesFilmic,
+            exposure: 0.0,
+        }
+    }
+}
+
+impl ToneMapper {
+    pub fn new(operator: ToneMapOperator, exposure: f32) -> Self {
+        ToneMapper { operator, exposure }
+    }
+
+    pub fn map(self, color: Color) -> Color {
+        let exposed = color * self.exposure.exp2();
+        self.operator.apply(exposed).to_srgb()
+    }
+
+    pub fn to_hex(self, color: Color) -> u32 {
+        self.map(color).to_hex()
+    }
+}
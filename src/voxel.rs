@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use nalgebra_glm::Vec3;
+
+use crate::bvh::{Aabb, inverse_direction};
+use crate::color::Color;
+use crate::cube::FaceMaterials;
+use crate::material::Material;
+use crate::ray_intersect::{RayIntersect, Intersect, Face};
+use crate::scene::ObjectProperties;
+
+// Identificador de bloque; 0 siempre es aire
+pub type BlockId = u16;
+pub const AIR: BlockId = 0;
+
+const CHUNK_SIZE: i32 = 16;
+const CHUNK_VOLUME: usize = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+// Tipo de bloque registrado en la paleta del mundo
+#[derive(Debug, Clone)]
+pub struct BlockType {
+    pub materials: FaceMaterials,
+    pub properties: ObjectProperties,
+}
+
+struct Chunk {
+    blocks: Vec<BlockId>,
+    solid_count: usize,
+}
+
+impl Chunk {
+    fn new() -> Self {
+        Chunk {
+            blocks: vec![AIR; CHUNK_VOLUME],
+            solid_count: 0,
+        }
+    }
+}
+
+// Mundo de bloques dividido en chunks de 16x16x16, recorrido con DDA (Amanatides–Woo)
+pub struct VoxelGrid {
+    pub block_size: f32,
+    chunks: HashMap<(i32, i32, i32), Chunk>,
+    palette: Vec<BlockType>,
+    min_block: [i32; 3],
+    max_block: [i32; 3],
+}
+
+fn chunk_key(x: i32, y: i32, z: i32) -> ((i32, i32, i32), usize) {
+    let key = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE), z.div_euclid(CHUNK_SIZE));
+    let (lx, ly, lz) = (x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE), z.rem_euclid(CHUNK_SIZE));
+    let index = ((ly * CHUNK_SIZE + lz) * CHUNK_SIZE + lx) as usize;
+    (key, index)
+}
+
+// Eje cuyo próximo borde de bloque está más cerca a lo largo del rayo
+fn nearest_axis(t_max: &[f32; 3]) -> usize {
+    if t_max[0] < t_max[1] {
+        if t_max[0] < t_max[2] { 0 } else { 2 }
+    } else if t_max[1] < t_max[2] {
+        1
+    } else {
+        2
+    }
+}
+
+impl VoxelGrid {
+    pub fn new(block_size: f32) -> Self {
+        VoxelGrid {
+            block_size,
+            chunks: HashMap::new(),
+            // La entrada 0 de la paleta corresponde al aire y nunca se dibuja
+            palette: vec![BlockType {
+                materials: FaceMaterials::uniform(Material::black()),
+                properties: ObjectProperties::new(false, true),
+            }],
+            min_block: [i32::MAX; 3],
+            max_block: [i32::MIN; 3],
+        }
+    }
+
+    // Registra un tipo de bloque y devuelve su identificador
+    pub fn register_block(&mut self, material: Material, properties: ObjectProperties) -> BlockId {
+        self.register_block_faces(FaceMaterials::uniform(material), properties)
+    }
+
+    // Igual que register_block, con un material distinto por cara
+    pub fn register_block_faces(&mut self, materials: FaceMaterials, properties: ObjectProperties) -> BlockId {
+        let id = self.palette.len();
+        assert!(id <= BlockId::MAX as usize, "demasiados tipos de bloque");
+        self.palette.push(BlockType { materials, properties });
+        id as BlockId
+    }
+
+    pub fn block_type(&self, id: BlockId) -> Option<&BlockType> {
+        self.palette.get(id as usize)
+    }
+
+    pub fn get(&self, x: i32, y: i32, z: i32) -> BlockId {
+        let (key, index) = chunk_key(x, y, z);
+        self.chunks.get(&key).map_or(AIR, |chunk| chunk.blocks[index])
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, id: BlockId) {
+        assert!((id as usize) < self.palette.len(), "bloque {} no registrado", id);
+        let (key, index) = chunk_key(x, y, z);
+
+        if id == AIR {
+            if let Some(chunk) = self.chunks.get_mut(&key) {
+                if chunk.blocks[index] != AIR {
+                    chunk.blocks[index] = AIR;
+                    chunk.solid_count -= 1;
+                }
+                if chunk.solid_count == 0 {
+                    self.chunks.remove(&key);
+                }
+            }
+            return;
+        }
+
+        let chunk = self.chunks.entry(key).or_insert_with(Chunk::new);
+        if chunk.blocks[index] == AIR {
+            chunk.solid_count += 1;
+        }
+        chunk.blocks[index] = id;
+
+        // Los límites solo crecen; quitar bloques no los reduce
+        for (axis, value) in [x, y, z].into_iter().enumerate() {
+            self.min_block[axis] = self.min_block[axis].min(value);
+            self.max_block[axis] = self.max_block[axis].max(value);
+        }
+    }
+
+    pub fn remove(&mut self, x: i32, y: i32, z: i32) {
+        self.set(x, y, z, AIR);
+    }
+
+    // Llena la caja [min, max) con el bloque dado
+    pub fn fill(&mut self, min: [i32; 3], max: [i32; 3], id: BlockId) {
+        for y in min[1]..max[1] {
+            for z in min[2]..max[2] {
+                for x in min[0]..max[0] {
+                    self.set(x, y, z, id);
+                }
+            }
+        }
+    }
+
+    // Caras emisivas descubiertas de los bloques, en coordenadas del mundo: la cara, las esquinas
+    // del rectángulo que cubre (plano, sobre la cara) y la emisión de esa cara. En cada capa de
+    // un chunk las caras iguales vecinas se juntan en rectángulos, así un lago de lava es un solo
+    // panel por lado y no una luz por bloque. Una cara tapada por un bloque opaco no ilumina.
+    pub fn emissive_faces(&self) -> Vec<(Face, Vec3, Vec3, Color)> {
+        let emission: Vec<[Color; 6]> = self
+            .palette
+            .iter()
+            .map(|block| Face::ALL.map(|face| block.materials.get(face).average_emission()))
+            .collect();
+        // Orden fijo de chunks para que las luces salgan siempre en el mismo orden
+        let mut keys: Vec<_> = self.chunks.keys().copied().collect();
+        keys.sort();
+
+        let mut faces = Vec::new();
+        for (cx, cy, cz) in keys {
+            let chunk = &self.chunks[&(cx, cy, cz)];
+            let origin = [cx * CHUNK_SIZE, cy * CHUNK_SIZE, cz * CHUNK_SIZE];
+            for face in Face::ALL {
+                let axis = face.axis();
+                let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+                let outward = if face.normal()[axis] > 0.0 { 1 } else { 0 };
+                let opposite = Face::from_axis(axis, outward == 0);
+
+                for layer in 0..CHUNK_SIZE {
+                    // Bloque del mundo en la posición (i, j) de la capa
+                    let block_at = |i: i32, j: i32| {
+                        let mut block = origin;
+                        block[axis] += layer;
+                        block[a] += i;
+                        block[b] += j;
+                        block
+                    };
+                    // Bloque cuya cara brilla en (i, j), si no está tapada
+                    let glowing = |i: i32, j: i32| {
+                        let block = block_at(i, j);
+                        let (_, index) = chunk_key(block[0], block[1], block[2]);
+                        let id = chunk.blocks[index];
+                        if emission[id as usize][face.index()].is_black() {
+                            return None;
+                        }
+                        let mut next = block;
+                        next[axis] += outward * 2 - 1;
+                        let neighbour = self.get(next[0], next[1], next[2]);
+                        let covered = neighbour != AIR && self.palette[neighbour as usize].materials.get(opposite).albedo[3] <= 0.0;
+                        (!covered).then_some(id)
+                    };
+
+                    let mut taken = vec![false; (CHUNK_SIZE * CHUNK_SIZE) as usize];
+                    let free = |taken: &[bool], i: i32, j: i32, id: BlockId| {
+                        !taken[(j * CHUNK_SIZE + i) as usize] && glowing(i, j) == Some(id)
+                    };
+                    for j in 0..CHUNK_SIZE {
+                        for i in 0..CHUNK_SIZE {
+                            let Some(id) = glowing(i, j).filter(|_| !taken[(j * CHUNK_SIZE + i) as usize]) else {
+                                continue;
+                            };
+                            let mut end_i = i + 1;
+                            while end_i < CHUNK_SIZE && free(&taken, end_i, j, id) {
+                                end_i += 1;
+                            }
+                            let mut end_j = j + 1;
+                            while end_j < CHUNK_SIZE && (i..end_i).all(|x| free(&taken, x, end_j, id)) {
+                                end_j += 1;
+                            }
+                            for y in j..end_j {
+                                for x in i..end_i {
+                                    taken[(y * CHUNK_SIZE + x) as usize] = true;
+                                }
+                            }
+
+                            let (start, end) = (block_at(i, j), block_at(end_i, end_j));
+                            let mut min = Vec3::new(start[0] as f32, start[1] as f32, start[2] as f32);
+                            let mut max = Vec3::new(end[0] as f32, end[1] as f32, end[2] as f32);
+                            min[axis] += outward as f32;
+                            max[axis] = min[axis];
+                            faces.push((face, min * self.block_size, max * self.block_size, emission[id as usize][face.index()]));
+                        }
+                    }
+                }
+            }
+        }
+        faces
+    }
+
+    pub fn block_count(&self) -> usize {
+        self.chunks.values().map(|chunk| chunk.solid_count).sum()
+    }
+
+    // Coordenada del bloque que contiene un punto del mundo
+    pub fn block_at(&self, point: &Vec3) -> [i32; 3] {
+        [
+            (point.x / self.block_size).floor() as i32,
+            (point.y / self.block_size).floor() as i32,
+            (point.z / self.block_size).floor() as i32,
+        ]
+    }
+
+    fn get_uv(&self, point: &Vec3, block: [i32; 3], axis: usize) -> (f32, f32) {
+        let local = point / self.block_size - Vec3::new(block[0] as f32, block[1] as f32, block[2] as f32);
+        // Misma orientación de texturas que Cube::get_uv
+        match axis {
+            0 => (local.z, local.y),
+            1 => (local.x, local.z),
+            _ => (local.x, local.y),
+        }
+    }
+
+    // Intersección con una cara del bloque; None si cae en un hueco recortado por alfa
+    fn block_hit(&self, ray_origin: &Vec3, ray_direction: &Vec3, t: f32, block: [i32; 3], block_type: &BlockType, face: Face) -> Option<Intersect> {
+        let point = ray_origin + ray_direction * t;
+        let (u, v) = self.get_uv(&point, block, face.axis());
+        let material = block_type.materials.get(face);
+        if material.is_cutout_at(u, v) {
+            return None;
+        }
+        let (tangent, bitangent) = face.tangents();
+        let normal = material.perturb_normal(&face.normal(), &tangent, &bitangent, u, v);
+        Some(
+            Intersect::new(point, normal, t, material.clone(), u, v)
+                .with_face(face)
+                .with_uv_scale(1.0 / self.block_size),
+        )
+    }
+
+    // Paso por eje y distancias al primer borde y entre bordes de bloque, desde `block`
+    fn dda_start(&self, ray_origin: &Vec3, ray_direction: &Vec3, block: [i32; 3]) -> ([i32; 3], [f32; 3], [f32; 3]) {
+        let inv_dir = inverse_direction(ray_direction);
+        let mut step = [0i32; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            let d = ray_direction[axis];
+            if d > 0.0 {
+                step[axis] = 1;
+                t_max[axis] = ((block[axis] + 1) as f32 * self.block_size - ray_origin[axis]) * inv_dir[axis];
+                t_delta[axis] = self.block_size * inv_dir[axis];
+            } else if d < 0.0 {
+                step[axis] = -1;
+                t_max[axis] = (block[axis] as f32 * self.block_size - ray_origin[axis]) * inv_dir[axis];
+                t_delta[axis] = -self.block_size * inv_dir[axis];
+            }
+        }
+        (step, t_max, t_delta)
+    }
+
+    // Recorre los bloques atravesados por el rayo y devuelve el primero que acepte `accept`
+    fn traverse<F>(&self, ray_origin: &Vec3, ray_direction: &Vec3, accept: F) -> Intersect
+    where
+        F: Fn(&BlockType) -> bool,
+    {
+        if self.chunks.is_empty() {
+            return Intersect::empty();
+        }
+
+        let bounds = self.bounding_box();
+        let inv_dir = inverse_direction(ray_direction);
+
+        // Distancia de entrada al volumen del mundo y eje de la cara de entrada
+        let mut t_enter = 0.0f32;
+        let mut t_exit = f32::INFINITY;
+        let mut entry_axis = None;
+        for axis in 0..3 {
+            let t0 = (bounds.min[axis] - ray_origin[axis]) * inv_dir[axis];
+            let t1 = (bounds.max[axis] - ray_origin[axis]) * inv_dir[axis];
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            if t0 > t_enter {
+                t_enter = t0;
+                entry_axis = Some(axis);
+            }
+            t_exit = t_exit.min(t1);
+        }
+        if t_enter > t_exit {
+            return Intersect::empty();
+        }
+
+        let entry_point = ray_origin + ray_direction * t_enter;
+        let mut block = self.block_at(&entry_point);
+        for (axis, value) in block.iter_mut().enumerate() {
+            *value = (*value).clamp(self.min_block[axis], self.max_block[axis]);
+        }
+
+        let (step, mut t_max, t_delta) = self.dda_start(ray_origin, ray_direction, block);
+
+        let mut t = t_enter;
+        let mut axis = entry_axis;
+        // Un rayo que nace dentro de un bloque (refractado) está en el medio que forman los
+        // bloques iguales vecinos, como el agua: no choca con las caras entre ellos sino con la
+        // cara por la que sale del medio, con la normal exterior, igual que Cube
+        let medium = if entry_axis.is_none() { self.get(block[0], block[1], block[2]) } else { AIR };
+        let mut inside = medium != AIR;
+
+        loop {
+            let id = self.get(block[0], block[1], block[2]);
+            if inside && id != medium {
+                inside = false;
+                let block_type = &self.palette[medium as usize];
+                if let Some(exit_axis) = axis.filter(|_| accept(block_type)) {
+                    let mut last = block;
+                    last[exit_axis] -= step[exit_axis];
+                    let face = Face::from_axis(exit_axis, step[exit_axis] > 0);
+                    if let Some(hit) = self.block_hit(ray_origin, ray_direction, t, last, block_type, face) {
+                        return hit;
+                    }
+                }
+            }
+            if id != AIR && !inside {
+                let block_type = &self.palette[id as usize];
+                if accept(block_type) {
+                    let hit_axis = axis.unwrap_or(0);
+                    let face = Face::from_axis(hit_axis, ray_direction[hit_axis] <= 0.0);
+                    if let Some(hit) = self.block_hit(ray_origin, ray_direction, t, block, block_type, face) {
+                        return hit;
+                    }
+
+                    // La cara de entrada es un hueco recortado: prueba la cara por donde sale el rayo,
+                    // que se ve desde adentro y por eso su normal apunta hacia el bloque
+                    let exit_axis = nearest_axis(&t_max);
+                    let face = Face::from_axis(exit_axis, step[exit_axis] > 0);
+                    let t_out = t_max[exit_axis];
+                    if let Some(mut hit) = self.block_hit(ray_origin, ray_direction, t_out, block, block_type, face) {
+                        hit.normal = -hit.normal;
+                        return hit;
+                    }
+                }
+            }
+
+            // Avanza al siguiente bloque por el eje cuyo borde está más cerca; dentro de un medio
+            // se sigue hasta el bloque de afuera para encontrar su cara de salida
+            let next_axis = nearest_axis(&t_max);
+            t = t_max[next_axis];
+            if t > t_exit && (!inside || !t.is_finite()) {
+                return Intersect::empty();
+            }
+            block[next_axis] += step[next_axis];
+            t_max[next_axis] += t_delta[next_axis];
+            axis = Some(next_axis);
+        }
+    }
+}
+
+impl RayIntersect for VoxelGrid {
+    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.traverse(ray_origin, ray_direction, |_| true)
+    }
+
+    fn shadow_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+        self.traverse(ray_origin, ray_direction, |block| block.properties.casts_shadow)
+    }
+
+    // Un volumen de bloques iguales (p. ej. agua) cuenta como un solo medio
+    fn exit_distance(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Option<f32> {
+        let mut block = self.block_at(ray_origin);
+        let id = self.get(block[0], block[1], block[2]);
+        if id == AIR {
+            return None;
+        }
+
+        let (step, mut t_max, t_delta) = self.dda_start(ray_origin, ray_direction, block);
+        loop {
+            let axis = nearest_axis(&t_max);
+            if step[axis] == 0 {
+                return None;
+            }
+            block[axis] += step[axis];
+            if self.get(block[0], block[1], block[2]) != id {
+                return Some(t_max[axis]);
+            }
+            t_max[axis] += t_delta[axis];
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        if self.chunks.is_empty() {
+            return Aabb::empty();
+        }
+        let min = Vec3::new(self.min_block[0] as f32, self.min_block[1] as f32, self.min_block[2] as f32);
+        let max = Vec3::new(
+            (self.max_block[0] + 1) as f32,
+            (self.max_block[1] + 1) as f32,
+            (self.max_block[2] + 1) as f32,
+        );
+        Aabb::new(min * self.block_size, max * self.block_size)
+    }
+}
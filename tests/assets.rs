@@ -0,0 +1,101 @@
+// Errores de carga de texturas y AssetManager
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use computer_graphics_v3::assets::AssetManager;
+use computer_graphics_v3::scene_loader::{load_scene_with, LoadOptions, SceneError};
+use computer_graphics_v3::texture::{ColorSpace, Texture, TextureError};
+use nalgebra_glm::Vec3;
+
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn assets() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("assets")
+}
+
+#[test]
+fn load_reports_missing_files() {
+    let path = fixture_dir("missing_texture").join("nope.png");
+    match Texture::load(&path) {
+        Err(TextureError::NotFound { path: reported }) => assert_eq!(reported, path),
+        other => panic!("se esperaba NotFound, se obtuvo {:?}", other.err()),
+    }
+}
+
+#[test]
+fn load_reports_corrupt_images() {
+    let path = fixture_dir("corrupt_texture").join("broken.png");
+    fs::write(&path, b"esto no es un png").unwrap();
+    let error = Texture::load(&path).expect_err("debería fallar");
+    assert!(matches!(error, TextureError::Decode { .. }), "{:?}", error);
+    assert_eq!(error.path(), path);
+    assert!(error.to_string().contains("broken.png"));
+}
+
+#[test]
+fn load_reports_unsupported_formats() {
+    let path = fixture_dir("unsupported_texture").join("notes.txt");
+    fs::write(&path, b"texto plano").unwrap();
+    let error = Texture::load(&path).expect_err("debería fallar");
+    assert!(matches!(error, TextureError::UnsupportedFormat { .. }), "{:?}", error);
+}
+
+#[test]
+fn load_decodes_existing_textures() {
+    let texture = Texture::load(assets().join("dirt.jpg")).unwrap();
+    assert_eq!((texture.width, texture.height), (128, 128));
+    assert!(!texture.is_placeholder());
+}
+
+#[test]
+fn asset_manager_loads_each_file_once() {
+    let mut manager = AssetManager::new(false);
+    let first = manager.texture(assets().join("dirt.jpg"), ColorSpace::Srgb);
+    let second = manager.texture(assets().join("dirt.jpg"), ColorSpace::Srgb);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(manager.finish().unwrap().is_empty());
+}
+
+const SCENE: &str = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+texture dirt  ASSETS/dirt.jpg
+texture stone missing_stone.png
+texture ore   missing_ore.png
+material stone texture=stone specular=1 albedo=0.9,0.1,0,0
+cube min=-1,-1,-1 max=1,1,1 material=stone
+";
+
+fn write_scene(name: &str) -> PathBuf {
+    let path = fixture_dir(name).join("test.scene");
+    fs::write(&path, SCENE.replace("ASSETS", &assets().to_string_lossy())).unwrap();
+    path
+}
+
+#[test]
+fn scenes_report_every_missing_asset_at_once() {
+    let path = write_scene("scene_missing_assets");
+    match load_scene_with(&path, LoadOptions::default()) {
+        Err(SceneError::Assets(missing)) => {
+            assert_eq!(missing.0.len(), 2);
+            let message = missing.to_string();
+            assert!(message.contains("missing_stone.png") && message.contains("missing_ore.png"), "{}", message);
+        }
+        other => panic!("se esperaba un error de recursos, se obtuvo {:?}", other.err()),
+    }
+}
+
+#[test]
+fn placeholders_replace_missing_assets() {
+    let path = write_scene("scene_placeholders");
+    let loaded = load_scene_with(&path, LoadOptions { placeholders: true }).unwrap();
+    assert_eq!(loaded.missing_assets.len(), 2);
+
+    let origin = Vec3::new(0.0, 0.0, 5.0);
+    let hit = loaded.scene.intersect(&origin, &-origin.normalize());
+    assert!(hit.material.texture.as_ref().unwrap().is_placeholder());
+}
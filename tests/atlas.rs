@@ -0,0 +1,111 @@
+// Atlas de texturas: cuadrícula, regiones con nombre y mosaicos en materiales
+use std::fs;
+use std::path::PathBuf;
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::scene_loader::load_scene;
+
+const CELL: u32 = 4;
+// Colores de las celdas de una cuadrícula de 2x2
+const COLORS: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 0]];
+// Rugosidad de las mismas celdas
+const ROUGHNESS: [u8; 4] = [0, 51, 153, 255];
+
+// Escribe el atlas, su archivo de mosaicos y una escena en un directorio temporal
+fn write_fixture(name: &str, tiles: &str, materials: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&dir).unwrap();
+
+    let image = image::RgbImage::from_fn(CELL * 2, CELL * 2, |x, y| {
+        image::Rgb(COLORS[((y / CELL) * 2 + x / CELL) as usize])
+    });
+    image.save(dir.join("atlas.png")).unwrap();
+    // Mapa de rugosidad a media resolución con la misma cuadrícula: un valor por celda
+    let half = CELL / 2;
+    let rough = image::GrayImage::from_fn(CELL, CELL, |x, y| {
+        image::Luma([ROUGHNESS[((y / half) * 2 + x / half) as usize]])
+    });
+    rough.save(dir.join("rough.png")).unwrap();
+    fs::write(dir.join("atlas.tiles"), tiles).unwrap();
+
+    let scene = format!(
+        "camera eye=0,0,5 center=0,0,0 up=0,1,0\natlas blocks atlas.png tiles=atlas.tiles\n\
+         texture rough rough.png space=linear filter=bilinear\n{}\n",
+        materials
+    );
+    let path = dir.join("test.scene");
+    fs::write(&path, scene).unwrap();
+    path
+}
+
+fn texture_color(index: usize) -> Color {
+    let [r, g, b] = COLORS[index];
+    Color::from_srgb8(r, g, b)
+}
+
+#[test]
+fn materials_sample_only_their_tile() {
+    let path = write_fixture(
+        "atlas_tiles",
+        "grid 2 2\nred 0 0\nyellow 1 1  # celda inferior derecha\nblue 0 4 4 4\n",
+        "material red texture=blocks tile=red specular=1 albedo=1,0,0,0\n\
+         material yellow texture=blocks tile=yellow specular=1 albedo=1,0,0,0\n\
+         material blue texture=blocks tile=blue specular=1 albedo=1,0,0,0\n\
+         material green texture=blocks tile=1,0 specular=1 albedo=1,0,0,0\n\
+         cube min=-1,-1,-1 max=1,1,1 material=red top=yellow bottom=blue sides=green",
+    );
+    let loaded = load_scene(&path).unwrap();
+    let hit = |origin: Vec3| loaded.scene.intersect(&origin, &-origin.normalize());
+
+    // Las esquinas de cada mosaico no deben tomar color de los vecinos
+    for (origin, expected) in [
+        (Vec3::new(0.0, 5.0, 0.0), 3),
+        (Vec3::new(0.0, -5.0, 0.0), 2),
+        (Vec3::new(0.0, 0.0, 5.0), 1),
+    ] {
+        let hit = hit(origin);
+        assert!(hit.is_intersecting);
+        for (u, v) in [(0.0, 0.0), (1.0, 1.0), (0.0, 1.0), (1.0, 0.0), (0.5, 0.5)] {
+            assert_eq!(hit.material.get_diffuse_color(u, v, 0.0), texture_color(expected), "uv {},{}", u, v);
+        }
+    }
+}
+
+#[test]
+fn data_maps_follow_the_material_tile() {
+    let path = write_fixture(
+        "atlas_maps",
+        "grid 2 2\nblue 0 1\n",
+        "material blue texture=blocks tile=blue roughness=1 roughness_map=rough\n\
+         cube min=-1,-1,-1 max=1,1,1 material=blue",
+    );
+    let loaded = load_scene(&path).unwrap();
+    let hit = loaded.scene.intersect(&Vec3::new(0.0, 0.0, 5.0), &-Vec3::z());
+    assert!(hit.is_intersecting);
+
+    // Con o sin filtrar, el valor sale solo de la celda del mosaico y no del resto del mapa
+    let expected = ROUGHNESS[2] as f32 / 255.0;
+    for footprint in [0.0, 0.5, 4.0] {
+        let surface = hit.material.surface_at(0.5, 0.5, footprint).expect("material físico");
+        assert!((surface.roughness - expected).abs() < 1e-3, "footprint {}: {}", footprint, surface.roughness);
+    }
+}
+
+#[test]
+fn unknown_tile_is_an_error() {
+    let path = write_fixture(
+        "atlas_unknown",
+        "grid 2 2\n",
+        "material stone texture=blocks tile=stone specular=1 albedo=1,0,0,0",
+    );
+    let error = load_scene(&path).err().expect("debería fallar");
+    assert!(error.to_string().contains("stone"), "{}", error);
+}
+
+#[test]
+fn tiles_outside_the_image_are_rejected() {
+    let path = write_fixture("atlas_bounds", "wide 4 0 8 4\n", "");
+    let error = load_scene(&path).err().expect("debería fallar");
+    assert!(error.to_string().contains("línea 1"), "{}", error);
+}
@@ -0,0 +1,115 @@
+// Modelo metálico-rugoso: GGX, Fresnel y lectura desde la escena
+use std::f32::consts::PI;
+use std::path::Path;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::brdf::{dielectric_f0, fresnel_schlick, ggx_distribution, sample_ggx_normal, SurfaceSample};
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene_loader::parse_scene;
+
+const EPSILON: f32 = 1e-3;
+
+fn surface(metallic: f32, roughness: f32) -> SurfaceSample {
+    SurfaceSample {
+        base_color: Color::new(1.0, 0.5, 0.25),
+        metallic,
+        roughness,
+        ior: 1.5,
+    }
+}
+
+#[test]
+fn ggx_distribution_is_normalized() {
+    // La integral de D(h) cos(h) sobre el hemisferio vale 1 para cualquier rugosidad
+    for roughness in [0.2f32, 0.5, 1.0] {
+        let alpha = roughness * roughness;
+        let steps = 20_000;
+        let mut integral = 0.0;
+        for i in 0..steps {
+            let theta = (i as f32 + 0.5) / steps as f32 * PI / 2.0;
+            integral += ggx_distribution(theta.cos(), alpha) * theta.cos() * theta.sin() * 2.0 * PI * (PI / 2.0 / steps as f32);
+        }
+        assert!((integral - 1.0).abs() < 0.01, "rugosidad {}: {}", roughness, integral);
+    }
+}
+
+#[test]
+fn fresnel_goes_from_f0_to_white() {
+    let f0 = Color::white() * dielectric_f0(1.5);
+    assert!((f0.r - 0.04).abs() < EPSILON);
+    assert!((fresnel_schlick(f0, 1.0).r - 0.04).abs() < EPSILON);
+    assert!((fresnel_schlick(f0, 0.0).r - 1.0).abs() < EPSILON);
+}
+
+#[test]
+fn metals_reflect_their_base_color_and_have_no_diffuse() {
+    let metal = surface(1.0, 0.5);
+    assert_eq!(metal.f0(), Color::new(1.0, 0.5, 0.25));
+
+    // Lejos del reflejo especular un metal es casi negro; un dieléctrico sigue siendo difuso
+    let normal = Vec3::y();
+    let view = Vec3::new(1.0, 1.0, 0.0).normalize();
+    let light = Vec3::new(1.0, 0.2, 0.0).normalize();
+    let metal = surface(1.0, 0.1).evaluate(&normal, &view, &light);
+    let dielectric = surface(0.0, 0.1).evaluate(&normal, &view, &light);
+    assert!(metal.luminance() < 0.01 * dielectric.luminance(), "{} vs {}", metal, dielectric);
+}
+
+#[test]
+fn rough_dielectric_is_close_to_lambert() {
+    // Luz y cámara de frente: difusa (1 - F0) / π más un especular ancho y débil
+    let lambert = surface(0.0, 1.0).evaluate(&Vec3::y(), &Vec3::y(), &Vec3::y()) * PI;
+    assert!(lambert.r > 0.9 && lambert.r < 1.1, "{}", lambert);
+    assert!(surface(0.0, 1.0).evaluate(&Vec3::y(), &Vec3::y(), &-Vec3::y()).is_black());
+}
+
+#[test]
+fn scene_materials_pick_the_model_from_their_keys() {
+    let source = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+material gold diffuse=255,200,80 metallic=1 roughness=0.3
+material red  diffuse=200,40,40 specular=10 albedo=0.9,0.1,0,0
+sphere center=-1,0,0 radius=0.5 material=gold
+sphere center=1,0,0 radius=0.5 material=red
+";
+    let loaded = parse_scene(source, Path::new(".")).unwrap();
+    let material_at = |x: f32| loaded.scene.intersect(&Vec3::new(x, 0.0, 5.0), &-Vec3::z()).material;
+
+    let gold = material_at(-1.0).pbr.expect("gold debería ser PBR");
+    assert_eq!((gold.metallic, gold.roughness), (1.0, 0.3));
+    assert!(material_at(1.0).pbr.is_none());
+
+    // En el modelo clásico `roughness` solo vuelve borrosos los reflejos
+    let glossy = source.replace("albedo=0.9,0.1,0,0", "albedo=0.6,0.1,0.3,0 roughness=0.2");
+    let loaded = parse_scene(&glossy, Path::new(".")).unwrap();
+    let red = loaded.scene.intersect(&Vec3::new(1.0, 0.0, 5.0), &-Vec3::z()).material;
+    assert!(red.pbr.is_none());
+    assert_eq!(red.roughness, 0.2);
+
+    let mixed = source.replace("metallic=1 roughness=0.3", "metallic=1 albedo=0.9,0.1,0,0");
+    let error = parse_scene(&mixed, Path::new(".")).err().expect("debería fallar");
+    assert!(error.to_string().contains("albedo"), "{}", error);
+}
+
+#[test]
+fn ggx_samples_spread_with_roughness() {
+    let normal = Vec3::new(0.3, 1.0, -0.2).normalize();
+    let mut rng = Rng::new(1, 2);
+    let mean_angle = |roughness: f32, rng: &mut Rng| {
+        let mut total = 0.0;
+        for _ in 0..1000 {
+            let h = sample_ggx_normal(&normal, roughness * roughness, rng.next_f32(), rng.next_f32());
+            assert!((h.magnitude() - 1.0).abs() < EPSILON);
+            assert!(h.dot(&normal) > 0.0, "la microfaceta quedó bajo la superficie");
+            total += h.dot(&normal).clamp(-1.0, 1.0).acos();
+        }
+        total / 1000.0
+    };
+
+    let polished = mean_angle(0.05, &mut rng);
+    let rough = mean_angle(0.6, &mut rng);
+    assert!(polished < 0.01, "{}", polished);
+    assert!(rough > 10.0 * polished, "{} vs {}", rough, polished);
+}
@@ -0,0 +1,48 @@
+// Utilidades compartidas por las pruebas de integración. Cada archivo de pruebas es un crate
+// aparte que usa solo algunas, así que las demás no cuentan como código muerto.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::Cube;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::Intersect;
+
+// Albedo de un material mate que no deja pasar la luz
+pub const OPAQUE: [f32; 4] = [0.9, 0.1, 0.0, 0.0];
+
+pub fn assert_vec_eq(actual: Vec3, expected: Vec3) {
+    assert!((actual - expected).magnitude() < 1e-4, "se esperaba {:?}, se obtuvo {:?}", expected, actual);
+}
+
+// Punto de un suelo con normal +y, listo para lanzar rayos de sombra desde él
+pub fn shaded_point(position: Vec3) -> Intersect {
+    Intersect::new(position, Vec3::y(), 0.0, Material::black(), 0.0, 0.0)
+}
+
+// Caja blanca que tapa la luz según su albedo (con albedo[3] > 0 deja pasar parte)
+pub fn blocker(min: Vec3, max: Vec3, albedo: [f32; 4]) -> Cube {
+    Cube::new(min, max, Material::new(Color::white(), 1.0, albedo, 1.5))
+}
+
+// Losa de 2x1x2 centrada sobre el origen, con la cara de abajo a la altura `y`
+pub fn slab(y: f32, albedo: [f32; 4]) -> Cube {
+    blocker(Vec3::new(-1.0, y, -1.0), Vec3::new(1.0, y + 1.0, 1.0), albedo)
+}
+
+// Escribe en el directorio temporal de las pruebas la imagen `name`, una sola vez aunque las
+// pruebas corran en paralelo, y devuelve su ruta
+pub fn temp_image(name: &str, image: impl FnOnce() -> image::DynamicImage) -> PathBuf {
+    static WRITTEN: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let mut written = WRITTEN.lock().unwrap();
+    if !written.iter().any(|written| written == name) {
+        image().save(&path).unwrap();
+        written.push(name.to_string());
+    }
+    path
+}
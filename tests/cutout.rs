@@ -0,0 +1,114 @@
+// Texturas RGBA y materiales con recorte por alfa
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::atlas::Tile;
+use computer_graphics_v3::castray::cast_shadow;
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::Cube;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::RayIntersect;
+use computer_graphics_v3::scene::{ObjectProperties, Scene};
+use computer_graphics_v3::square::Square;
+use computer_graphics_v3::texture::Texture;
+use computer_graphics_v3::voxel::VoxelGrid;
+
+mod common;
+use common::{assert_vec_eq, shaded_point, temp_image};
+
+const EPSILON: f32 = 1e-4;
+
+// Textura de 2x1: mitad izquierda roja y opaca, mitad derecha transparente
+fn half_transparent() -> Arc<Texture> {
+    let path = temp_image("half_transparent.png", || {
+        let pixels = [[255, 0, 0, 255], [0, 0, 255, 0]];
+        image::RgbaImage::from_fn(2, 1, |x, _| image::Rgba(pixels[x as usize])).into()
+    });
+    Arc::new(Texture::new_linear(&path.to_string_lossy()))
+}
+
+fn cutout_material() -> Material {
+    Material::new_with_texture(1.0, [0.9, 0.1, 0.0, 0.0], 0.0, half_transparent()).with_alpha_cutout(0.5)
+}
+
+fn unit_cube() -> Cube {
+    Cube::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0), cutout_material())
+}
+
+#[test]
+fn rgba_textures_keep_their_alpha() {
+    let texture = half_transparent();
+    let full = Tile::full(&texture);
+    assert!(texture.has_alpha);
+    assert!((texture.sample_alpha(0.25, 0.5, &full) - 1.0).abs() < EPSILON);
+    assert!(texture.sample_alpha(0.75, 0.5, &full).abs() < EPSILON);
+    // El color de un texel transparente se conserva
+    assert_eq!(texture.sample(0.75, 0.5), Color::new(0.0, 0.0, 1.0));
+}
+
+#[test]
+fn materials_without_cutoff_are_always_solid() {
+    let material = Material::new_with_texture(1.0, [0.9, 0.1, 0.0, 0.0], 0.0, half_transparent());
+    assert!(!material.is_cutout_at(0.75, 0.5));
+    assert!(cutout_material().is_cutout_at(0.75, 0.5));
+    assert!(!cutout_material().is_cutout_at(0.25, 0.5));
+}
+
+#[test]
+fn cube_opaque_texels_are_hit() {
+    let hit = unit_cube().ray_intersect(&Vec3::new(-0.5, 0.0, 5.0), &-Vec3::z());
+    assert!(hit.is_intersecting);
+    assert_vec_eq(hit.normal, Vec3::z());
+}
+
+#[test]
+fn cube_rays_through_a_hole_hit_the_far_face_from_inside() {
+    // Entra por la mitad transparente de la cara delantera y sale por la mitad opaca de la trasera
+    let origin = Vec3::new(1.5, 0.0, 3.0);
+    let direction = Vec3::new(-1.0, 0.0, -2.0).normalize();
+    let hit = unit_cube().ray_intersect(&origin, &direction);
+
+    assert!(hit.is_intersecting);
+    assert!((hit.point.z + 1.0).abs() < EPSILON);
+    assert_vec_eq(hit.normal, Vec3::z());
+
+    // Si ambas caras son huecos, el rayo atraviesa el cubo
+    assert!(!unit_cube().ray_intersect(&Vec3::new(0.5, 0.0, 5.0), &-Vec3::z()).is_intersecting);
+}
+
+#[test]
+fn voxel_rays_pass_through_holes() {
+    let mut grid = VoxelGrid::new(1.0);
+    let id = grid.register_block(cutout_material(), ObjectProperties::default());
+    grid.set(0, 0, 0, id);
+
+    let solid = grid.ray_intersect(&Vec3::new(0.25, 0.5, 5.0), &-Vec3::z());
+    assert!(solid.is_intersecting);
+    assert_vec_eq(solid.normal, Vec3::z());
+
+    assert!(!grid.ray_intersect(&Vec3::new(0.75, 0.5, 5.0), &-Vec3::z()).is_intersecting);
+
+    // Entra por el hueco y choca con la mitad opaca de la cara trasera, vista desde adentro
+    let direction = Vec3::new(-1.0, 0.0, -2.0).normalize();
+    let hit = grid.ray_intersect(&Vec3::new(1.75, 0.5, 3.0), &direction);
+    assert!(hit.is_intersecting);
+    assert!(hit.point.z.abs() < EPSILON);
+    assert_vec_eq(hit.normal, Vec3::z());
+}
+
+#[test]
+fn shadows_pass_through_holes() {
+    let mut scene = Scene::new();
+    let material = cutout_material();
+    scene.add(Square::new(Vec3::new(0.0, 4.0, 0.0), Vec3::y(), 2.0, material), ObjectProperties::default());
+    let light = Light::new(Vec3::new(0.0, 10.0, 0.0), Color::white(), 1.0);
+
+    let shaded = |x: f32| {
+        cast_shadow(&shaded_point(Vec3::new(x, 0.0, 0.0)), &light, &scene)
+    };
+    // En un cuadrado horizontal u crece hacia -x: la mitad transparente queda en x < 0
+    assert_eq!(shaded(0.5), 1.0);
+    assert_eq!(shaded(-0.5), 0.0);
+}
@@ -0,0 +1,146 @@
+// Materiales emisivos: se ven con su propia luz y se registran solos como luces de área
+use std::path::Path;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::castray::{cast_ray, cast_shadow, sample_shadow, RayCone};
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::light::{Falloff, Light, LightKind};
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::Face;
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene::ObjectProperties;
+use computer_graphics_v3::scene_loader::parse_scene;
+use computer_graphics_v3::voxel::VoxelGrid;
+
+mod common;
+use common::shaded_point;
+
+const CAMERA: &str = "camera eye=0,0,5 center=0,0,0 up=0,1,0\n";
+const LAMP: &str = "material lamp diffuse=0,0,0 specular=1 albedo=1,0,0,0 emission=255,128,0 emission_strength=2\n";
+
+#[test]
+fn emission_adds_to_the_surface_color() {
+    // Sin luces en la escena, una esfera negra solo muestra su emisión
+    let source = format!("{}{}sphere center=0,0,0 radius=1 material=lamp\n", CAMERA, LAMP);
+    let loaded = parse_scene(&source, Path::new(".")).unwrap();
+    let mut rng = Rng::new(1, 1);
+    let color = cast_ray(&Vec3::new(0.0, 0.0, 5.0), &-Vec3::z(), &loaded.scene, &[], 0, RayCone::none(), &mut rng);
+    let expected = Color::from_srgb8(255, 128, 0) * 2.0;
+    let difference = color - expected;
+    assert!(difference.r.abs().max(difference.g.abs()).max(difference.b.abs()) < 1e-3, "{} vs {}", color, expected);
+
+    assert!(Material::black().average_emission().is_black());
+}
+
+#[test]
+fn emissive_shapes_become_area_lights() {
+    let source = format!(
+        "{}{}material plain diffuse=200,200,200 specular=1 albedo=0.9,0.1,0,0
+cube min=0,0,0 max=1,1,1 material=lamp
+sphere center=3,0,0 radius=0.5 material=lamp
+square center=0,-1,0 normal=0,1,0 size=2 material=lamp
+cube min=5,0,0 max=6,1,1 material=plain
+cube min=7,0,0 max=8,1,1 material=plain top=lamp
+",
+        CAMERA, LAMP
+    );
+    let loaded = parse_scene(&source, Path::new(".")).unwrap();
+    assert_eq!(loaded.lights.len(), 4);
+    assert!(matches!(loaded.lights[0].kind, LightKind::Cube { falloff: Falloff::Range(range), .. } if range == 4.0));
+    assert!(matches!(loaded.lights[1].kind, LightKind::Sphere { radius, .. } if radius == 0.5));
+    assert!(matches!(loaded.lights[2].kind, LightKind::Rect { .. }));
+
+    // El color es el de la emisión y la intensidad su componente más fuerte
+    let lamp = &loaded.lights[0];
+    assert!((lamp.intensity - 2.0).abs() < 1e-4);
+    assert_eq!(lamp.color.r, 1.0);
+    assert!(lamp.samples > 1);
+
+    // Con una sola cara emisiva solo esa cara es luz: un panel sobre la tapa que mira hacia arriba
+    let LightKind::Rect { center, edge_u, edge_v, .. } = loaded.lights[3].kind else {
+        panic!("se esperaba un panel: {:?}", loaded.lights[3].kind);
+    };
+    assert_eq!(center, Vec3::new(7.5, 1.0, 0.5));
+    assert_eq!(edge_u.cross(&edge_v), Vec3::y());
+    assert!((loaded.lights[3].intensity - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn emission_without_texture_needs_a_color() {
+    let source = format!("{}material lamp diffuse=0,0,0 specular=1 albedo=1,0,0,0 emission_strength=2\n", CAMERA);
+    let error = parse_scene(&source, Path::new(".")).err().expect("debería fallar");
+    assert!(error.to_string().contains("emission"), "{}", error);
+}
+
+#[test]
+fn emissive_block_faces_merge_into_rectangles() {
+    let lamp = Material::new(Color::black(), 1.0, [1.0, 0.0, 0.0, 0.0], 0.0).with_emission(Color::white(), 1.0);
+    let stone = Material::new(Color::white(), 1.0, [1.0, 0.0, 0.0, 0.0], 0.0);
+    let mut grid = VoxelGrid::new(0.5);
+    let lava = grid.register_block(lamp, ObjectProperties::default());
+    let rock = grid.register_block(stone, ObjectProperties::default());
+    grid.fill([0, 0, 0], [3, 1, 2], lava);
+    grid.set(3, 0, 0, rock);
+    grid.set(4, 0, 0, lava);
+    grid.set(0, 0, 2, lava);
+
+    // Las tapas se juntan en rectángulos: dos para la zona de 3x2 con la fila de más, y el bloque
+    // tras la roca
+    let faces = grid.emissive_faces();
+    let corners = |face: Face| -> Vec<(Vec3, Vec3)> {
+        faces.iter().filter(|f| f.0 == face).map(|f| (f.1, f.2)).collect()
+    };
+    assert_eq!(
+        corners(Face::Top),
+        [
+            (Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.5, 0.5, 1.5)),
+            (Vec3::new(0.5, 0.5, 0.0), Vec3::new(1.5, 0.5, 1.0)),
+            (Vec3::new(2.0, 0.5, 0.0), Vec3::new(2.5, 0.5, 0.5)),
+        ]
+    );
+    // La roca tapa la cara derecha del primer bloque de la fila; la de atrás sigue descubierta
+    assert!(corners(Face::Right).contains(&(Vec3::new(1.5, 0.0, 0.5), Vec3::new(1.5, 0.5, 1.0))));
+    assert!(!corners(Face::Right).iter().any(|(min, _)| *min == Vec3::new(1.5, 0.0, 0.0)));
+    assert!(faces.iter().all(|face| face.3 == Color::white()));
+}
+
+#[test]
+fn emissive_block_faces_light_on_their_own() {
+    // Solo las tapas brillan: cada bloque ilumina hacia arriba con la emisión completa
+    let source = format!(
+        "{}{}material plain diffuse=200,200,200 specular=1 albedo=0.9,0.1,0,0
+world block_size=1
+block lamp_top material=plain top=lamp
+fill 0..2 0..1 0..1 lamp_top
+",
+        CAMERA, LAMP
+    );
+    let loaded = parse_scene(&source, Path::new(".")).unwrap();
+    assert_eq!(loaded.lights.len(), 1);
+    let LightKind::Rect { center, edge_u, edge_v, .. } = loaded.lights[0].kind else {
+        panic!("se esperaba un panel: {:?}", loaded.lights[0].kind);
+    };
+    assert_eq!(center, Vec3::new(1.0, 1.0, 0.5));
+    assert_eq!(edge_u.cross(&edge_v).normalize(), Vec3::y());
+    assert!((loaded.lights[0].intensity - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn emissive_blocks_do_not_shadow_their_own_light() {
+    let source = format!(
+        "{}{}world block_size=1\nblock lava material=lamp\nfill 0..2 0..1 0..1 lava\n",
+        CAMERA, LAMP
+    );
+    let loaded = parse_scene(&source, Path::new(".")).unwrap();
+    // Un panel por lado de la fila
+    assert_eq!(loaded.lights.len(), 6);
+
+    // Un punto del suelo junto al bloque ve la luz completa, aunque el bloque sí tapa luces
+    let beside = shaded_point(Vec3::new(-0.5, 0.0, 0.5));
+    let mut rng = Rng::new(2, 3);
+    assert_eq!(sample_shadow(&beside, &loaded.lights[0], &loaded.scene, &mut rng), 0.0);
+
+    let behind = Light::new(Vec3::new(3.0, 0.5, 0.5), Color::white(), 1.0);
+    assert_eq!(cast_shadow(&beside, &behind, &loaded.scene), 1.0);
+}
@@ -0,0 +1,167 @@
+// Pruebas de imagen de referencia: renderiza escenas pequeñas sin ventana y las compara
+// con los PNG guardados en tests/golden/. Para regenerarlas tras un cambio intencional:
+//
+//   UPDATE_GOLDEN=1 cargo test --test golden
+use std::env;
+use std::path::{Path, PathBuf};
+
+use computer_graphics_v3::framebuffer::Framebuffer;
+use computer_graphics_v3::render::{render, Integrator, RenderSettings};
+use computer_graphics_v3::sampling::{SamplePattern, PixelFilter};
+use computer_graphics_v3::scene_loader::load_scene;
+
+// Diferencia media máxima (ΔE CIE76) sobre toda la imagen
+const MAX_MEAN_DELTA_E: f32 = 1.0;
+// Una diferencia mayor a esta ya es claramente visible
+const VISIBLE_DELTA_E: f32 = 10.0;
+// Fracción de píxeles que pueden diferir visiblemente (bordes que cambian de lado por redondeo)
+const MAX_VISIBLE_FRACTION: f32 = 0.005;
+
+fn project_path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn render_scene(scene_path: &str, width: usize, height: usize, settings: &RenderSettings) -> Vec<u8> {
+    let loaded = load_scene(project_path(scene_path)).expect("no se pudo cargar la escena");
+    let mut framebuffer = Framebuffer::new(width, height);
+    render(&mut framebuffer, &loaded.scene, &loaded.camera, &loaded.lights, settings);
+    framebuffer.to_rgb_bytes()
+}
+
+// sRGB de 8 bits -> CIELAB (iluminante D65)
+fn to_lab(rgb: &[u8]) -> [f32; 3] {
+    let linear = |c: u8| computer_graphics_v3::color::srgb_to_linear(c as f32 / 255.0);
+    let (r, g, b) = (linear(rgb[0]), linear(rgb[1]), linear(rgb[2]));
+
+    let x = (0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b) / 0.950_47;
+    let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+    let z = (0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b) / 1.088_83;
+
+    let f = |t: f32| if t > 0.008_856 { t.cbrt() } else { 7.787 * t + 16.0 / 116.0 };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn delta_e(a: &[u8], b: &[u8]) -> f32 {
+    let (a, b) = (to_lab(a), to_lab(b));
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+fn check_golden(name: &str, width: usize, height: usize, actual: &[u8]) {
+    let golden_path = project_path(&format!("tests/golden/{}.png", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        image::save_buffer(&golden_path, actual, width as u32, height as u32, image::ExtendedColorType::Rgb8)
+            .expect("no se pudo guardar la imagen de referencia");
+        return;
+    }
+
+    let expected = image::open(&golden_path)
+        .unwrap_or_else(|error| panic!("{}: {} (genérala con UPDATE_GOLDEN=1)", golden_path.display(), error))
+        .to_rgb8();
+    assert_eq!(
+        (expected.width() as usize, expected.height() as usize),
+        (width, height),
+        "{}: tamaño distinto al de la referencia",
+        name
+    );
+
+    let differences: Vec<f32> = actual
+        .chunks_exact(3)
+        .zip(expected.as_raw().chunks_exact(3))
+        .map(|(a, b)| delta_e(a, b))
+        .collect();
+    let mean = differences.iter().sum::<f32>() / differences.len() as f32;
+    let visible = differences.iter().filter(|&&d| d > VISIBLE_DELTA_E).count() as f32 / differences.len() as f32;
+
+    if mean > MAX_MEAN_DELTA_E || visible > MAX_VISIBLE_FRACTION {
+        // Deja el resultado junto a los artefactos de prueba para compararlo a mano
+        let actual_path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.png", name));
+        let _ = image::save_buffer(&actual_path, actual, width as u32, height as u32, image::ExtendedColorType::Rgb8);
+        panic!(
+            "{}: ΔE medio {:.3}, {:.2}% de píxeles con diferencia visible; resultado en {}",
+            name,
+            mean,
+            visible * 100.0,
+            actual_path.display()
+        );
+    }
+}
+
+#[test]
+fn golden_primitives() {
+    let settings = RenderSettings::default();
+    let image = render_scene("tests/golden/primitives.scene", 96, 72, &settings);
+    check_golden("primitives", 96, 72, &image);
+}
+
+#[test]
+fn golden_primitives_antialiased() {
+    let settings = RenderSettings {
+        samples: 4,
+        pattern: SamplePattern::Stratified,
+        filter: PixelFilter::Gaussian,
+        seed: 7,
+        ..RenderSettings::default()
+    };
+    let image = render_scene("tests/golden/primitives.scene", 96, 72, &settings);
+    check_golden("primitives_aa", 96, 72, &image);
+}
+
+#[test]
+fn golden_diorama() {
+    let settings = RenderSettings::default();
+    let image = render_scene("scenes/diorama.scene", 96, 72, &settings);
+    check_golden("diorama", 96, 72, &image);
+}
+
+#[test]
+fn golden_comparison_detects_changes() {
+    // La tolerancia no debe ser tan amplia que acepte una imagen distinta
+    let black = [0u8, 0, 0];
+    let gray = [128u8, 128, 128];
+    assert!(delta_e(&black, &black) < 1e-3);
+    assert!(delta_e(&black, &gray) > VISIBLE_DELTA_E);
+    assert!(delta_e(&[100, 100, 100], &[101, 100, 100]) < 1.0);
+}
+
+#[test]
+fn golden_pbr() {
+    let settings = RenderSettings::default();
+    let image = render_scene("tests/golden/pbr.scene", 96, 72, &settings);
+    check_golden("pbr", 96, 72, &image);
+}
+
+#[test]
+fn golden_glossy() {
+    let settings = RenderSettings::default();
+    let image = render_scene("tests/golden/glossy.scene", 96, 72, &settings);
+    check_golden("glossy", 96, 72, &image);
+}
+
+#[test]
+fn golden_lights() {
+    let settings = RenderSettings::default();
+    let image = render_scene("tests/golden/lights.scene", 96, 72, &settings);
+    check_golden("lights", 96, 72, &image);
+}
+
+#[test]
+fn golden_area_lights() {
+    let settings = RenderSettings::default();
+    let image = render_scene("tests/golden/area.scene", 96, 72, &settings);
+    check_golden("area", 96, 72, &image);
+}
+
+#[test]
+fn golden_path_tracer() {
+    // Con pocas muestras queda ruido, pero la semilla fija lo hace reproducible
+    let settings = RenderSettings {
+        samples: 16,
+        pattern: SamplePattern::Stratified,
+        integrator: Integrator::PathTracer,
+        ..RenderSettings::default()
+    };
+    let image = render_scene("tests/golden/pathtrace.scene", 48, 36, &settings);
+    check_golden("pathtrace", 48, 36, &image);
+}
@@ -0,0 +1,203 @@
+// Casos borde de las intersecciones de Cube y Sphere
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::{Cube, FaceMaterials};
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::{RayIntersect, Face};
+use computer_graphics_v3::sphere::Sphere;
+
+mod common;
+use common::assert_vec_eq;
+
+const EPSILON: f32 = 1e-4;
+
+fn material() -> Material {
+    Material::new(Color::white(), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0)
+}
+
+fn unit_cube() -> Cube {
+    Cube::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0), material())
+}
+
+fn unit_sphere() -> Sphere {
+    Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, material())
+}
+
+#[test]
+fn cube_axis_parallel_rays_hit_every_face() {
+    let cube = unit_cube();
+    let axes = [Vec3::x(), Vec3::y(), Vec3::z()];
+
+    for axis in axes {
+        for sign in [1.0, -1.0] {
+            let normal = axis * sign;
+            let hit = cube.ray_intersect(&(normal * 5.0), &-normal);
+
+            assert!(hit.is_intersecting, "el rayo desde {:?} debería chocar", normal * 5.0);
+            assert!((hit.distance - 4.0).abs() < EPSILON);
+            assert_vec_eq(hit.point, normal);
+            assert_vec_eq(hit.normal, normal);
+        }
+    }
+}
+
+#[test]
+fn cube_reports_uv_on_the_hit_face() {
+    let cube = unit_cube();
+    let hit = cube.ray_intersect(&Vec3::new(0.5, -0.5, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+
+    assert!(hit.is_intersecting);
+    assert!((hit.u - 0.75).abs() < EPSILON);
+    assert!((hit.v - 0.25).abs() < EPSILON);
+}
+
+#[test]
+fn cube_oblique_ray_hits_the_nearest_face() {
+    let cube = unit_cube();
+    let origin = Vec3::new(3.0, 0.5, 0.2);
+    let direction = Vec3::new(-1.0, -0.1, 0.05).normalize();
+    let hit = cube.ray_intersect(&origin, &direction);
+
+    assert!(hit.is_intersecting);
+    assert_vec_eq(hit.normal, Vec3::x());
+    assert!((hit.point.x - 1.0).abs() < EPSILON);
+    assert_vec_eq(origin + direction * hit.distance, hit.point);
+}
+
+#[test]
+fn cube_rays_starting_inside_hit_the_far_face() {
+    // Un rayo refractado nace dentro y debe encontrar la cara por la que sale, con la normal
+    // exterior para que el trazador sepa que sale del vidrio
+    let cube = unit_cube();
+    let origin = Vec3::new(0.2, -0.3, 0.1);
+    for (direction, face, distance) in [
+        (Vec3::x(), Face::Right, 0.8),
+        (-Vec3::y(), Face::Bottom, 0.7),
+        (Vec3::new(1.0, 1.0, 1.0).normalize(), Face::Right, 0.8 * 3f32.sqrt()),
+    ] {
+        let hit = cube.ray_intersect(&origin, &direction);
+        assert!(hit.is_intersecting);
+        assert_eq!(hit.face, Some(face));
+        assert!((hit.distance - distance).abs() < EPSILON, "{}", hit.distance);
+        assert!(hit.normal.dot(&direction) > 0.0);
+    }
+}
+
+#[test]
+fn cube_ignores_hits_behind_the_origin() {
+    let cube = unit_cube();
+    let hit = cube.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, 1.0));
+    assert!(!hit.is_intersecting);
+}
+
+#[test]
+fn cube_grazing_rays() {
+    let cube = unit_cube();
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    // Justo dentro del borde superior choca con la cara delantera; justo fuera, no
+    let inside = cube.ray_intersect(&Vec3::new(0.0, 0.999, 5.0), &direction);
+    assert!(inside.is_intersecting);
+    assert_vec_eq(inside.normal, Vec3::z());
+    assert!(!cube.ray_intersect(&Vec3::new(0.0, 1.001, 5.0), &direction).is_intersecting);
+
+    // Un rayo paralelo a una cara, fuera de su losa, nunca entra
+    assert!(!cube.ray_intersect(&Vec3::new(-5.0, 2.0, 0.0), &Vec3::x()).is_intersecting);
+}
+
+#[test]
+fn cube_ray_through_an_edge() {
+    let cube = unit_cube();
+    let direction = Vec3::new(-1.0, -1.0, 0.0).normalize();
+    let hit = cube.ray_intersect(&Vec3::new(3.0, 3.0, 0.0), &direction);
+
+    assert!(hit.is_intersecting);
+    assert_vec_eq(hit.point, Vec3::new(1.0, 1.0, 0.0));
+    assert!((hit.normal.magnitude() - 1.0).abs() < EPSILON);
+}
+
+#[test]
+fn sphere_head_on_hit() {
+    let sphere = unit_sphere();
+    let hit = sphere.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+
+    assert!(hit.is_intersecting);
+    assert!((hit.distance - 4.0).abs() < EPSILON);
+    assert_vec_eq(hit.point, Vec3::z());
+    assert_vec_eq(hit.normal, Vec3::z());
+}
+
+#[test]
+fn sphere_axis_parallel_rays_from_every_side() {
+    let sphere = Sphere::new(Vec3::new(1.0, -2.0, 3.0), 0.5, material());
+    for axis in [Vec3::x(), Vec3::y(), Vec3::z(), -Vec3::x(), -Vec3::y(), -Vec3::z()] {
+        let hit = sphere.ray_intersect(&(sphere.center + axis * 4.0), &-axis);
+
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 3.5).abs() < EPSILON);
+        assert_vec_eq(hit.normal, axis);
+    }
+}
+
+#[test]
+fn sphere_rays_starting_inside_hit_the_far_side() {
+    let sphere = unit_sphere();
+    let hit = sphere.ray_intersect(&Vec3::new(0.0, 0.6, 0.0), &Vec3::new(0.0, 0.0, -1.0));
+    assert!(hit.is_intersecting);
+    assert!((hit.distance - 0.8).abs() < EPSILON, "{}", hit.distance);
+    assert_vec_eq(hit.normal, Vec3::new(0.0, 0.6, -0.8));
+}
+
+#[test]
+fn sphere_ignores_hits_behind_the_origin() {
+    let sphere = unit_sphere();
+    let hit = sphere.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, 1.0));
+    assert!(!hit.is_intersecting);
+}
+
+#[test]
+fn sphere_grazing_rays() {
+    let sphere = unit_sphere();
+    let direction = Vec3::new(0.0, 0.0, -1.0);
+
+    let inside = sphere.ray_intersect(&Vec3::new(0.999, 0.0, 5.0), &direction);
+    assert!(inside.is_intersecting);
+    // La normal queda casi perpendicular al rayo
+    assert!(inside.normal.dot(&direction).abs() < 0.05);
+    assert!((inside.normal.magnitude() - 1.0).abs() < EPSILON);
+
+    assert!(!sphere.ray_intersect(&Vec3::new(1.001, 0.0, 5.0), &direction).is_intersecting);
+}
+
+#[test]
+fn sphere_distance_is_measured_along_unnormalized_directions() {
+    let sphere = unit_sphere();
+    let direction = Vec3::new(0.0, 0.0, -2.0);
+    let hit = sphere.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &direction);
+
+    assert!(hit.is_intersecting);
+    assert_vec_eq(Vec3::new(0.0, 0.0, 5.0) + direction * hit.distance, hit.point);
+}
+
+#[test]
+fn cube_reports_the_hit_face_and_its_material() {
+    let top = Material::new(Color::new(0.0, 1.0, 0.0), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0);
+    let materials = FaceMaterials::top_bottom_sides(top, material(), material());
+    let cube = Cube::with_face_materials(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0), materials);
+
+    for face in Face::ALL {
+        let hit = cube.ray_intersect(&(face.normal() * 5.0), &-face.normal());
+        assert_eq!(hit.face, Some(face));
+        assert_vec_eq(hit.normal, face.normal());
+        let expected = if face == Face::Top { Color::new(0.0, 1.0, 0.0) } else { Color::white() };
+        assert_eq!(hit.material.diffuse, expected, "material de la cara {}", face.name());
+    }
+}
+
+#[test]
+fn sphere_does_not_report_a_face() {
+    let hit = unit_sphere().ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &Vec3::new(0.0, 0.0, -1.0));
+    assert!(hit.is_intersecting);
+    assert_eq!(hit.face, None);
+}
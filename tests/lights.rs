@@ -0,0 +1,233 @@
+// Atenuación con la distancia, focos, cookies y luces de área
+use std::path::Path;
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::castray::{cast_ray, cast_shadow, sample_shadow, RayCone};
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::light::{Falloff, Light, LightKind};
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene::{Scene, ObjectProperties};
+use computer_graphics_v3::scene_loader::parse_scene;
+use computer_graphics_v3::sky::Sky;
+use computer_graphics_v3::square::Square;
+use computer_graphics_v3::texture::Texture;
+
+mod common;
+use common::{blocker, shaded_point, OPAQUE};
+
+const EPSILON: f32 = 1e-4;
+
+#[test]
+fn falloff_curves() {
+    assert_eq!(Falloff::None.at(100.0), 1.0);
+    assert!((Falloff::InverseSquare.at(2.0) - 0.25).abs() < EPSILON);
+    assert!((Falloff::InverseSquare.at(4.0) - 0.0625).abs() < EPSILON);
+
+    // El alcance parte de 1 junto a la luz, baja sin saltos y llega a cero en el borde
+    let range = Falloff::Range(3.0);
+    assert!((range.at(0.0) - 1.0).abs() < EPSILON);
+    assert!(range.at(1.0) > range.at(2.0) && range.at(2.0) > 0.0);
+    assert_eq!(range.at(3.0), 0.0);
+    assert_eq!(range.at(10.0), 0.0);
+}
+
+#[test]
+fn point_lights_attenuate_with_distance() {
+    let lamp = Light::new(Vec3::zeros(), Color::white(), 1.0);
+    assert_eq!(lamp.attenuation(&Vec3::new(50.0, 0.0, 0.0)), 1.0);
+
+    let lamp = lamp.with_falloff(Falloff::InverseSquare);
+    assert!((lamp.attenuation(&Vec3::new(0.0, 2.0, 0.0)) - 0.25).abs() < EPSILON);
+
+    // El sol no tiene distancia
+    let sun = Light::directional(Vec3::y(), Color::white(), 1.0, 0.0).with_falloff(Falloff::InverseSquare);
+    assert_eq!(sun.attenuation(&Vec3::new(0.0, -1000.0, 0.0)), 1.0);
+}
+
+#[test]
+fn spot_cone_fades_between_inner_and_outer() {
+    let spot = Light::spot(Vec3::new(0.0, 1.0, 0.0), -Vec3::y(), Color::white(), 1.0, 20f32.to_radians(), 30f32.to_radians());
+    // Punto del suelo a un ángulo dado del eje
+    let at = |degrees: f32| spot.attenuation(&Vec3::new(degrees.to_radians().tan(), 0.0, 0.0));
+
+    assert!((at(0.0) - 1.0).abs() < EPSILON);
+    assert!((at(19.0) - 1.0).abs() < EPSILON);
+    let middle = at(25.0);
+    assert!(middle > 0.1 && middle < 0.9, "{}", middle);
+    assert_eq!(at(31.0), 0.0);
+
+    // Detrás del foco no llega nada
+    assert_eq!(spot.attenuation(&Vec3::new(0.0, 2.0, 0.0)), 0.0);
+}
+
+#[test]
+fn cookie_tints_the_spot() {
+    let spot = Light::spot(Vec3::new(0.0, 1.0, 0.0), -Vec3::y(), Color::white(), 1.0, 0.0, 40f32.to_radians());
+    assert_eq!(spot.cookie_at(&Vec3::zeros()), Color::white());
+
+    // El tablero de ajedrez proyectado da colores distintos en distintas partes del cono
+    let spot = spot.with_cookie(Arc::new(Texture::checkerboard()));
+    let samples: Vec<Color> = (-4..=4)
+        .map(|i| spot.cookie_at(&Vec3::new(i as f32 * 0.1, 0.0, 0.05)))
+        .collect();
+    assert!(samples.iter().any(|c| *c != samples[0]), "{:?}", samples);
+}
+
+#[test]
+fn scene_lights_read_falloff_and_spots() {
+    let source = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+light position=0,4,0 range=3
+light position=0,4,0 falloff=inverse_square
+spot position=0,3,0 direction=0,-1,0 inner=10 outer=25 range=6
+";
+    let loaded = parse_scene(source, Path::new(".")).unwrap();
+    assert!(matches!(loaded.lights[0].kind, LightKind::Point { falloff: Falloff::Range(range), .. } if range == 3.0));
+    assert!(matches!(loaded.lights[1].kind, LightKind::Point { falloff: Falloff::InverseSquare, .. }));
+    match &loaded.lights[2].kind {
+        LightKind::Spot { direction, inner_angle, outer_angle, falloff, cookie, .. } => {
+            assert_eq!(*direction, -Vec3::y());
+            assert!((inner_angle - 10f32.to_radians()).abs() < EPSILON);
+            assert!((outer_angle - 25f32.to_radians()).abs() < EPSILON);
+            assert_eq!(*falloff, Falloff::Range(6.0));
+            assert!(cookie.is_none());
+        }
+        kind => panic!("se esperaba un foco: {:?}", kind),
+    }
+
+    for bad in [
+        "light position=0,4,0 range=3 falloff=inverse_square",
+        "light position=0,4,0 range=0",
+        "light position=0,4,0 falloff=linear",
+        "light direction=0,1,0 range=3",
+        "spot position=0,3,0 direction=0,-1,0 inner=30 outer=20",
+        "spot position=0,3,0 direction=0,-1,0 outer=20 cookie=none",
+    ] {
+        let scene = format!("camera eye=0,0,5 center=0,0,0 up=0,1,0\n{}\n", bad);
+        assert!(parse_scene(&scene, Path::new(".")).is_err(), "`{}` debería fallar", bad);
+    }
+}
+
+// Puntos donde los rayos de sombra de `light` apuntan desde el origen
+fn sampled_points(light: &Light, from: Vec3) -> Vec<Vec3> {
+    let mut rng = Rng::new(5, 6);
+    (0..200)
+        .map(|_| {
+            let (direction, distance) = light.sample_direction(&from, &mut rng);
+            from + direction * distance
+        })
+        .collect()
+}
+
+#[test]
+fn area_light_samples_lie_on_the_shape() {
+    let panel = Light::rect(Vec3::new(0.0, 3.0, 0.0), -Vec3::y(), 2.0, 1.0, Color::white(), 1.0);
+    for p in sampled_points(&panel, Vec3::zeros()) {
+        assert!((p.y - 3.0).abs() < 1e-3, "{:?}", p);
+        let (u, v) = (p.x.abs().max(p.z.abs()), p.x.abs().min(p.z.abs()));
+        assert!(u <= 1.0 + 1e-3 && v <= 1.0 + 1e-3, "{:?}", p);
+    }
+
+    let bulb = Light::sphere(Vec3::new(0.0, 2.0, 0.0), 0.5, Color::white(), 1.0);
+    for p in sampled_points(&bulb, Vec3::zeros()) {
+        assert!((p - Vec3::new(0.0, 2.0, 0.0)).magnitude() <= 0.5 + 1e-3, "{:?}", p);
+    }
+
+    // Desde abajo y a la izquierda solo se ven las caras inferior e izquierda del bloque
+    let block = Light::cube(Vec3::new(1.0, 1.0, -0.5), Vec3::new(2.0, 2.0, 0.5), Color::white(), 1.0);
+    let points = sampled_points(&block, Vec3::zeros());
+    for p in &points {
+        assert!((p.x - 1.0).abs() < 1e-3 || (p.y - 1.0).abs() < 1e-3, "{:?}", p);
+    }
+    assert!(points.iter().any(|p| (p.x - 1.0).abs() < 1e-3) && points.iter().any(|p| (p.y - 1.0).abs() < 1e-3));
+}
+
+#[test]
+fn panels_only_light_their_front() {
+    let panel = Light::rect(Vec3::new(0.0, 3.0, 0.0), -Vec3::y(), 2.0, 2.0, Color::white(), 1.0);
+    assert!((panel.attenuation(&Vec3::zeros()) - 1.0).abs() < EPSILON);
+    assert_eq!(panel.attenuation(&Vec3::new(0.0, 5.0, 0.0)), 0.0);
+    // De canto llega menos luz
+    assert!(panel.attenuation(&Vec3::new(3.0, 0.0, 0.0)) < panel.attenuation(&Vec3::zeros()));
+}
+
+#[test]
+fn area_lights_shade_from_each_sample() {
+    // Una pared que mira hacia +x bajo el borde de un panel ancho: el centro del panel queda
+    // detrás de ella (sombreada desde el centro sería negra), pero la franja del panel que la
+    // sobrepasa sí la ilumina
+    let mut scene = Scene::new();
+    let matte = Material::new(Color::white(), 1.0, [1.0, 0.0, 0.0, 0.0], 0.0);
+    scene.add(Square::new(Vec3::new(1.5, 0.5, 0.0), Vec3::x(), 1.0, matte), ObjectProperties::default());
+    scene.sky = Sky::uniform(Color::black());
+
+    let panel = Light::rect(Vec3::new(0.0, 1.0, 0.0), -Vec3::y(), 4.0, 4.0, Color::white(), 1.0);
+    let (origin, direction) = (Vec3::new(3.0, 0.5, 0.0), -Vec3::x());
+    let mut rng = Rng::new(1, 1);
+    let lights = [panel.with_samples(64)];
+    let color = cast_ray(&origin, &direction, &scene, &lights, 0, RayCone::none(), &mut rng);
+    assert!(color.r > 1e-3, "{}", color);
+}
+
+#[test]
+fn area_lights_cast_penumbras() {
+    // Un bloque que tapa la mitad del panel visto desde el punto
+    let mut scene = Scene::new();
+    scene.add(blocker(Vec3::new(-2.0, 1.0, -2.0), Vec3::new(0.0, 1.5, 2.0), OPAQUE), ObjectProperties::default());
+
+    let panel = Light::rect(Vec3::new(0.0, 3.0, 0.0), -Vec3::y(), 2.0, 2.0, Color::white(), 1.0).with_samples(256);
+    let mut rng = Rng::new(1, 1);
+    let shadow = sample_shadow(&shaded_point(Vec3::zeros()), &panel, &scene, &mut rng);
+    assert!((shadow - 0.5).abs() < 0.1, "{}", shadow);
+
+    // Una luz puntual solo da sombra o luz
+    let lamp = Light::new(Vec3::new(0.5, 3.0, 0.0), Color::white(), 1.0).with_samples(16);
+    assert_eq!(sample_shadow(&shaded_point(Vec3::zeros()), &lamp, &scene, &mut rng), 0.0);
+}
+
+#[test]
+fn transparent_surfaces_multiply_the_light_through() {
+    // Dos vidrios en el camino: pasa transparencia², sin importar su distancia a la luz
+    let glass = [0.2, 0.0, 0.0, 0.5];
+    let mut scene = Scene::new();
+    scene.add(blocker(Vec3::new(-1.0, 1.0, -1.0), Vec3::new(1.0, 2.0, 1.0), glass), ObjectProperties::new(true, true));
+    let lamp = Light::new(Vec3::new(0.0, 10.0, 0.0), Color::white(), 1.0);
+    assert!((cast_shadow(&shaded_point(Vec3::zeros()), &lamp, &scene) - 0.5).abs() < EPSILON);
+
+    scene.add(blocker(Vec3::new(-1.0, 8.0, -1.0), Vec3::new(1.0, 9.0, 1.0), glass), ObjectProperties::new(true, true));
+    assert!((cast_shadow(&shaded_point(Vec3::zeros()), &lamp, &scene) - 0.75).abs() < EPSILON);
+}
+
+#[test]
+fn scene_area_lights() {
+    let source = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+material lava diffuse=255,120,30 specular=1 albedo=1,0,0,0
+area rect center=0,3,0 normal=0,-1,0 size=2,1 samples=16
+area sphere center=2,1,0 radius=0.25 range=4
+area cube min=-1,0,-1 max=0,1,0 material=lava samples=8
+";
+    let loaded = parse_scene(source, Path::new(".")).unwrap();
+    assert!(matches!(loaded.lights[0].kind, LightKind::Rect { .. }));
+    assert_eq!(loaded.lights[0].samples, 16);
+    assert!(matches!(loaded.lights[1].kind, LightKind::Sphere { radius, falloff: Falloff::Range(_), .. } if radius == 0.25));
+    assert_eq!(loaded.lights[1].samples, 1);
+    assert!(matches!(loaded.lights[2].kind, LightKind::Cube { .. }));
+
+    // El bloque con material se ve, pero no tapa su propia luz
+    assert_eq!(loaded.scene.objects.len(), 1);
+    assert!(!loaded.scene.objects[0].properties.casts_shadow);
+
+    for bad in [
+        "area disc center=0,0,0",
+        "area rect center=0,3,0 normal=0,-1,0 size=2",
+        "area sphere center=0,3,0 radius=1 samples=0",
+        "area rect center=0,3,0 normal=0,-1,0 size=2,1 material=lava",
+    ] {
+        let scene = format!("camera eye=0,0,5 center=0,0,0 up=0,1,0\nmaterial lava diffuse=255,120,30 specular=1 albedo=1,0,0,0\n{}\n", bad);
+        assert!(parse_scene(&scene, Path::new(".")).is_err(), "`{}` debería fallar", bad);
+    }
+}
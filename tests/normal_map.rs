@@ -0,0 +1,95 @@
+// Mapas de normales en Cube, Square, Sphere y VoxelGrid
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::Cube;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::{Face, RayIntersect};
+use computer_graphics_v3::scene::ObjectProperties;
+use computer_graphics_v3::scene_loader::parse_scene;
+use computer_graphics_v3::sphere::Sphere;
+use computer_graphics_v3::square::Square;
+use computer_graphics_v3::texture::Texture;
+use computer_graphics_v3::voxel::VoxelGrid;
+
+mod common;
+use common::temp_image;
+
+const EPSILON: f32 = 1e-3;
+
+// Mapa de 1x1 con la normal inclinada 45° hacia +u: (1, 0, 1) en espacio tangente
+fn tilted_map_path() -> PathBuf {
+    temp_image("tilted_normal.png", || image::RgbImage::from_pixel(1, 1, image::Rgb([255, 128, 255])).into())
+}
+
+fn tilted(strength: f32) -> Material {
+    let normal_map = Arc::new(Texture::new_linear(&tilted_map_path().to_string_lossy()));
+    Material::new(Color::white(), 10.0, [0.9, 0.1, 0.0, 0.0], 0.0).with_normal_map(normal_map, strength)
+}
+
+// Ángulo en grados entre la normal obtenida y la geométrica
+fn tilt(normal: Vec3, geometric: Vec3) -> f32 {
+    normal.normalize().dot(&geometric).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+#[test]
+fn cube_faces_tilt_toward_their_u_direction() {
+    let cube = Cube::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0), tilted(1.0));
+    for face in Face::ALL {
+        let hit = cube.ray_intersect(&(face.normal() * 5.0), &-face.normal());
+        let (tangent, _) = face.tangents();
+
+        assert!((tilt(hit.normal, face.normal()) - 45.0).abs() < 0.5, "cara {}", face.name());
+        assert!(hit.normal.dot(&tangent) > 0.5, "cara {}: {:?}", face.name(), hit.normal);
+    }
+}
+
+#[test]
+fn strength_scales_the_tilt() {
+    let cube = |strength| Cube::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0), tilted(strength));
+    let normal_with = |strength| cube(strength).ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &-Vec3::z()).normal;
+
+    assert!(tilt(normal_with(0.0), Vec3::z()) < EPSILON);
+    let half = tilt(normal_with(0.5), Vec3::z());
+    assert!((half - 0.5f32.atan().to_degrees()).abs() < 0.5, "{}", half);
+}
+
+#[test]
+fn square_tilts_along_its_u_axis() {
+    let square = Square::new(Vec3::new(0.0, 0.0, 0.0), Vec3::z(), 2.0, tilted(1.0));
+    let hit = square.ray_intersect(&Vec3::new(0.0, 0.0, 5.0), &-Vec3::z());
+
+    // Para normales en Z, u crece hacia +x (ver Square::axes)
+    assert!((tilt(hit.normal, Vec3::z()) - 45.0).abs() < 0.5);
+    assert!(hit.normal.x > 0.5, "{:?}", hit.normal);
+}
+
+#[test]
+fn sphere_and_voxels_use_the_map() {
+    let sphere = Sphere::new(Vec3::new(0.0, 0.0, 0.0), 1.0, tilted(1.0));
+    let hit = sphere.ray_intersect(&Vec3::new(5.0, 0.0, 0.0), &-Vec3::x());
+    assert!((tilt(hit.normal, Vec3::x()) - 45.0).abs() < 0.5);
+
+    let mut grid = VoxelGrid::new(1.0);
+    let id = grid.register_block(tilted(1.0), ObjectProperties::default());
+    grid.set(0, 0, 0, id);
+    let hit = grid.ray_intersect(&Vec3::new(0.5, 5.0, 0.5), &-Vec3::y());
+    assert!(hit.normal.x > 0.5, "{:?}", hit.normal);
+}
+
+#[test]
+fn normal_maps_must_be_linear() {
+    let scene = |space: &str| {
+        format!(
+            "camera eye=0,0,5 center=0,0,0 up=0,1,0\ntexture bumps {} {}\nmaterial rock diffuse=255,255,255 specular=1 albedo=0.9,0.1,0,0 normal=bumps\n",
+            tilted_map_path().display(),
+            space
+        )
+    };
+    parse_scene(&scene("space=linear"), Path::new(".")).unwrap();
+    let error = parse_scene(&scene(""), Path::new(".")).err().expect("debería fallar");
+    assert!(error.to_string().contains("space=linear"), "{}", error);
+}
@@ -0,0 +1,117 @@
+// Trazador de caminos: muestreo de la BSDF y de las luces, y que converja a lo esperado
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::brdf::sample_cosine_direction;
+use computer_graphics_v3::castray::{cast_ray, RayCone};
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::pathtrace::trace_path;
+use computer_graphics_v3::render::Integrator;
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene::{Scene, ObjectProperties};
+use computer_graphics_v3::sky::Sky;
+use computer_graphics_v3::sphere::Sphere;
+use computer_graphics_v3::square::Square;
+
+fn matte(color: Color) -> Material {
+    Material::new(color, 1.0, [1.0, 0.0, 0.0, 0.0], 0.0)
+}
+
+// Suelo blanco mate en y = 0 bajo un cielo del color dado
+fn floor_scene(sky: Color) -> Scene {
+    let mut scene = Scene::new();
+    scene.add(Square::new(Vec3::zeros(), Vec3::y(), 20.0, matte(Color::white())), ObjectProperties::default());
+    scene.sky = Sky::uniform(sky);
+    scene
+}
+
+fn average_path(scene: &Scene, lights: &[Light], origin: Vec3, direction: Vec3, paths: u32) -> Color {
+    let mut rng = Rng::new(7, 11);
+    let mut sum = Color::black();
+    for _ in 0..paths {
+        sum += trace_path(&origin, &direction, scene, lights, RayCone::none(), &mut rng);
+    }
+    sum * (1.0 / paths as f32)
+}
+
+#[test]
+fn cosine_samples_stay_above_the_surface() {
+    let normal = Vec3::new(1.0, 2.0, -0.5).normalize();
+    let mut rng = Rng::new(1, 2);
+    let mut cosine_sum = 0.0;
+    for _ in 0..4000 {
+        let direction = sample_cosine_direction(&normal, rng.next_f32(), rng.next_f32());
+        assert!((direction.magnitude() - 1.0).abs() < 1e-4);
+        let cosine = direction.dot(&normal);
+        assert!(cosine >= 0.0, "{}", cosine);
+        cosine_sum += cosine;
+    }
+    // Con densidad cos / π el coseno promedio es 2/3
+    assert!((cosine_sum / 4000.0 - 2.0 / 3.0).abs() < 0.02, "{}", cosine_sum / 4000.0);
+}
+
+#[test]
+fn light_samples_report_the_density_they_were_drawn_with() {
+    let lights = [
+        Light::rect(Vec3::new(0.0, 3.0, 0.0), -Vec3::y(), 2.0, 1.0, Color::white(), 1.0),
+        Light::sphere(Vec3::new(1.0, 3.0, 0.0), 0.5, Color::white(), 1.0),
+        Light::cube(Vec3::new(2.0, 2.0, -1.0), Vec3::new(3.0, 3.0, 0.0), Color::white(), 1.0),
+    ];
+    let point = Vec3::new(0.2, 0.0, 0.3);
+    let mut rng = Rng::new(3, 5);
+    for light in &lights {
+        for _ in 0..100 {
+            let (direction, distance, pdf) = light.sample_surface(&point, &mut rng).expect("la luz se ve");
+            let target = point + direction * distance;
+            let expected = light.surface_pdf(&point, &target).expect("el punto está sobre la luz");
+            assert!((pdf - expected).abs() <= 1e-3 * expected, "{:?}: {} vs {}", light.kind, pdf, expected);
+        }
+    }
+
+    // Un panel no se ve desde atrás, y las luces puntuales no tienen superficie
+    assert!(lights[0].sample_surface(&Vec3::new(0.0, 5.0, 0.0), &mut rng).is_none());
+    assert!(Light::new(Vec3::y(), Color::white(), 1.0).sample_surface(&point, &mut rng).is_none());
+}
+
+#[test]
+fn white_floor_under_a_uniform_sky_reflects_the_sky() {
+    // Sin luces, un suelo blanco mate refleja todo el cielo: la imagen vale lo mismo que el cielo
+    let scene = floor_scene(Color::white() * 0.5);
+    let color = average_path(&scene, &[], Vec3::new(0.0, 2.0, 2.0), Vec3::new(0.0, -1.0, -1.0).normalize(), 64);
+    assert!((color.r - 0.5).abs() < 1e-3 && (color.b - 0.5).abs() < 1e-3, "{}", color);
+}
+
+#[test]
+fn point_lights_are_as_bright_as_in_whitted() {
+    let scene = floor_scene(Color::black());
+    let lights = [Light::new(Vec3::new(1.0, 3.0, 0.0), Color::white(), 0.8)];
+    let (origin, direction) = (Vec3::new(0.0, 2.0, 2.0), Vec3::new(0.0, -1.0, -1.0).normalize());
+
+    let mut rng = Rng::new(1, 1);
+    let whitted = cast_ray(&origin, &direction, &scene, &lights, 0, RayCone::none(), &mut rng);
+    let path = average_path(&scene, &lights, origin, direction, 16);
+    assert!((whitted.g - path.g).abs() < 1e-3, "{} vs {}", whitted, path);
+}
+
+#[test]
+fn emissive_sphere_lights_the_floor_without_bias() {
+    // Esfera de radiancia 1 y radio 0.5 a 2 unidades sobre el punto: E = π (r / d)², y un suelo
+    // blanco de Lambert devuelve E / π = 1/16
+    let mut scene = floor_scene(Color::black());
+    let lamp = matte(Color::black()).with_emission(Color::white(), 1.0);
+    scene.add(Sphere::new(Vec3::new(0.0, 2.0, 0.0), 0.5, lamp), ObjectProperties::default());
+    let lights = [Light::sphere(Vec3::new(0.0, 2.0, 0.0), 0.5, Color::white(), 1.0)];
+
+    let color = average_path(&scene, &lights, Vec3::new(0.0, 1.0, 1.0), Vec3::new(0.0, -1.0, -1.0).normalize(), 8000);
+    assert!((color.g - 1.0 / 16.0).abs() < 0.003, "{}", color);
+}
+
+#[test]
+fn integrators_have_names() {
+    for integrator in Integrator::ALL {
+        assert_eq!(Integrator::from_name(integrator.name()), Some(integrator));
+    }
+    assert_eq!(Integrator::Whitted.next(), Integrator::PathTracer);
+    assert_eq!(Integrator::from_name("bidirectional"), None);
+}
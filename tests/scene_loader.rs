@@ -0,0 +1,73 @@
+// Lectura del formato de escena
+use std::path::Path;
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::ray_intersect::Face;
+use computer_graphics_v3::scene_loader::parse_scene;
+
+const BLOCKS: &str = "
+camera eye=0,5,5 center=0,0,0 up=0,1,0
+material dirt  diffuse=120,80,40 specular=1 albedo=0.9,0.1,0,0
+material grass diffuse=60,160,40 specular=1 albedo=0.9,0.1,0,0
+material side  diffuse=90,120,40 specular=1 albedo=0.9,0.1,0,0
+world block_size=1
+block grass material=dirt top=grass sides=side
+set 0,0,0 grass
+cube min=3,0,0 max=4,1,1 material=dirt bottom=grass
+";
+
+#[test]
+fn blocks_and_cubes_take_per_face_materials() {
+    let loaded = parse_scene(BLOCKS, Path::new(".")).unwrap();
+    let diffuse_at = |origin: Vec3, direction: Vec3| {
+        let hit = loaded.scene.intersect(&origin, &direction);
+        assert!(hit.is_intersecting);
+        (hit.face.unwrap(), hit.material.diffuse)
+    };
+
+    assert_eq!(diffuse_at(Vec3::new(0.5, 5.0, 0.5), -Vec3::y()), (Face::Top, Color::from_srgb8(60, 160, 40)));
+    assert_eq!(diffuse_at(Vec3::new(0.5, -5.0, 0.5), Vec3::y()), (Face::Bottom, Color::from_srgb8(120, 80, 40)));
+    assert_eq!(diffuse_at(Vec3::new(0.5, 0.5, 5.0), -Vec3::z()), (Face::Front, Color::from_srgb8(90, 120, 40)));
+    assert_eq!(diffuse_at(Vec3::new(3.5, -5.0, 0.5), Vec3::y()), (Face::Bottom, Color::from_srgb8(60, 160, 40)));
+    assert_eq!(diffuse_at(Vec3::new(3.5, 5.0, 0.5), -Vec3::y()), (Face::Top, Color::from_srgb8(120, 80, 40)));
+}
+
+#[test]
+fn unknown_face_material_is_an_error() {
+    let source = BLOCKS.replace("top=grass", "top=stone");
+    let error = parse_scene(&source, Path::new(".")).err().expect("debería fallar");
+    assert!(error.to_string().contains("stone"), "{}", error);
+}
+
+#[test]
+fn blocks_reject_the_transparent_key() {
+    let source = BLOCKS.replace("sides=side", "sides=side transparent=true");
+    let error = parse_scene(&source, Path::new(".")).err().expect("debería fallar");
+    assert!(error.to_string().contains("transparent"), "{}", error);
+}
+
+// Carga una escena con solo la luz dada y revisa que falle en su línea, nombrando `key`
+fn assert_rejected(light: &str, key: &str) {
+    let source = format!("camera eye=0,5,5 center=0,0,0 up=0,1,0\n{}\n", light);
+    let error = parse_scene(&source, Path::new(".")).err().expect("debería fallar");
+    let message = error.to_string();
+    assert!(message.contains("línea 2") && message.contains(key), "{}: {}", light, message);
+}
+
+#[test]
+fn area_rects_need_a_positive_size() {
+    assert_rejected("area rect center=0,3,0 normal=0,-1,0 size=0,1", "size");
+    assert_rejected("area rect center=0,3,0 normal=0,-1,0 size=2,-1", "size");
+}
+
+#[test]
+fn area_rects_need_a_normal() {
+    assert_rejected("area rect center=0,3,0 normal=0,0,0 size=2,1", "normal");
+}
+
+#[test]
+fn area_spheres_need_a_positive_radius() {
+    assert_rejected("area sphere center=0,3,0 radius=0", "radius");
+    assert_rejected("area sphere center=0,3,0 radius=-0.5", "radius");
+}
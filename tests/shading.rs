@@ -0,0 +1,212 @@
+// reflect, refract y cast_shadow
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::brdf::fresnel_dielectric;
+use computer_graphics_v3::castray::{cast_ray, cast_shadow, reflect, refract, RayCone};
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::cube::Cube;
+use computer_graphics_v3::light::Light;
+use computer_graphics_v3::material::Material;
+use computer_graphics_v3::ray_intersect::Intersect;
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene::{Scene, ObjectProperties};
+use computer_graphics_v3::sky::Sky;
+use computer_graphics_v3::sphere::Sphere;
+use computer_graphics_v3::voxel::VoxelGrid;
+
+mod common;
+use common::{assert_vec_eq, shaded_point, slab, OPAQUE};
+
+const EPSILON: f32 = 1e-4;
+
+// Seno del ángulo entre una dirección y la normal
+fn sine(direction: &Vec3, normal: &Vec3) -> f32 {
+    direction.normalize().cross(normal).magnitude()
+}
+
+#[test]
+fn reflect_mirrors_around_the_normal() {
+    let incident = Vec3::new(1.0, -1.0, 0.0).normalize();
+    assert_vec_eq(reflect(&incident, &Vec3::y()), Vec3::new(1.0, 1.0, 0.0).normalize());
+}
+
+#[test]
+fn reflect_at_normal_incidence_reverses_the_ray() {
+    assert_vec_eq(reflect(&-Vec3::z(), &Vec3::z()), Vec3::z());
+}
+
+#[test]
+fn reflect_keeps_grazing_rays_unchanged() {
+    assert_vec_eq(reflect(&Vec3::x(), &Vec3::y()), Vec3::x());
+}
+
+#[test]
+fn reflect_preserves_length() {
+    let incident = Vec3::new(0.3, -2.0, 0.7);
+    assert!((reflect(&incident, &Vec3::y()).magnitude() - incident.magnitude()).abs() < EPSILON);
+}
+
+#[test]
+fn refract_at_normal_incidence_goes_straight() {
+    let normal = Vec3::z();
+    assert_vec_eq(refract(&-normal, &normal, 1.5), -normal);
+    // Saliendo del objeto la normal apunta en el mismo sentido que el rayo
+    assert_vec_eq(refract(&normal, &normal, 1.5), normal);
+}
+
+#[test]
+fn refract_entering_bends_toward_the_normal() {
+    // Aire -> vidrio: sen(t) = sen(i) / 1.5
+    let normal = Vec3::y();
+    let incident = Vec3::new(1.0, -1.0, 0.0).normalize();
+    let refracted = refract(&incident, &normal, 1.5);
+
+    assert!(refracted.y < 0.0, "el rayo debería seguir hacia adentro: {:?}", refracted);
+    assert!((refracted.magnitude() - 1.0).abs() < EPSILON);
+    assert!((sine(&refracted, &normal) - sine(&incident, &normal) / 1.5).abs() < EPSILON);
+}
+
+#[test]
+fn refract_leaving_bends_away_from_the_normal() {
+    // Vidrio -> aire: sen(t) = 1.5 * sen(i)
+    let normal = Vec3::y();
+    let incident = Vec3::new(0.3, 1.0, 0.0).normalize();
+    let refracted = refract(&incident, &normal, 1.5);
+
+    assert!(refracted.y > 0.0, "el rayo debería salir: {:?}", refracted);
+    assert!((sine(&refracted, &normal) - sine(&incident, &normal) * 1.5).abs() < EPSILON);
+}
+
+#[test]
+fn refract_total_internal_reflection() {
+    // Por encima del ángulo crítico (41.8° para 1.5) el rayo se queda adentro
+    let normal = Vec3::y();
+    let incident = Vec3::new(1.0, 0.5, 0.0).normalize();
+    assert_vec_eq(refract(&incident, &normal, 1.5), reflect(&incident, &-normal));
+}
+
+fn light_above() -> Light {
+    Light::new(Vec3::new(0.0, 10.0, 0.0), Color::white(), 1.0)
+}
+
+
+#[test]
+fn cast_shadow_unblocked_light() {
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &Scene::new()), 0.0);
+}
+
+#[test]
+fn cast_shadow_opaque_slab() {
+    let mut scene = Scene::new();
+    scene.add(slab(4.0, OPAQUE), ObjectProperties::default());
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene), 1.0);
+}
+
+#[test]
+fn cast_shadow_ignores_objects_beyond_the_light() {
+    let mut scene = Scene::new();
+    scene.add(slab(12.0, OPAQUE), ObjectProperties::default());
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene), 0.0);
+}
+
+#[test]
+fn cast_shadow_ignores_objects_without_shadow() {
+    let mut scene = Scene::new();
+    scene.add(slab(4.0, OPAQUE), ObjectProperties::new(false, false));
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene), 0.0);
+}
+
+#[test]
+fn cast_shadow_does_not_self_shadow() {
+    // El punto está sobre la cara superior de un cubo; el desplazamiento del origen
+    // evita que el rayo de sombra choque con esa misma cara
+    let mut scene = Scene::new();
+    scene.add(slab(-1.0, OPAQUE), ObjectProperties::default());
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene), 0.0);
+}
+
+#[test]
+fn cast_shadow_transparent_blocker_is_partial() {
+    let mut scene = Scene::new();
+    scene.add(slab(4.0, [0.8, 0.2, 0.1, 0.6]), ObjectProperties::new(true, true));
+    let shadow = cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene);
+    assert!(shadow > 0.0 && shadow < 1.0, "sombra parcial esperada, se obtuvo {}", shadow);
+}
+
+#[test]
+fn cast_shadow_with_bvh_matches_linear() {
+    let mut scene = Scene::new();
+    for i in 0..8 {
+        scene.add(slab(2.0 + i as f32, [0.8, 0.2, 0.1, 0.3]), ObjectProperties::new(true, true));
+    }
+    let linear = cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene);
+    scene.build_bvh();
+    assert_eq!(cast_shadow(&shaded_point(Vec3::zeros()), &light_above(), &scene), linear);
+}
+
+#[test]
+fn ray_cone_footprint_grows_with_distance_and_grazing_angle() {
+    let hit = Intersect::new(Vec3::new(0.0, 0.0, 0.0), Vec3::y(), 4.0, Material::black(), 0.0, 0.0).with_uv_scale(0.5);
+    let cone = RayCone::new(0.01).at(4.0);
+    assert!((cone.width - 0.04).abs() < EPSILON);
+
+    let head_on = cone.footprint(&hit, &-Vec3::y());
+    assert!((head_on - 0.02).abs() < EPSILON);
+    assert!(cone.footprint(&hit, &Vec3::new(1.0, -0.2, 0.0)) > head_on);
+    assert_eq!(RayCone::none().at(100.0).footprint(&hit, &-Vec3::y()), 0.0);
+}
+
+#[test]
+fn fresnel_grows_toward_grazing_angles() {
+    // Vidrio de frente: ((1.5 - 1) / (1.5 + 1))² = 4 %
+    assert!((fresnel_dielectric(1.0, 1.0, 1.5) - 0.04).abs() < EPSILON);
+    assert!(fresnel_dielectric(0.5, 1.0, 1.5) > 0.04);
+    assert!((fresnel_dielectric(0.0, 1.0, 1.5) - 1.0).abs() < EPSILON);
+    // Desde adentro, más allá del ángulo crítico, todo se refleja
+    assert_eq!(fresnel_dielectric(0.5, 1.5, 1.0), 1.0);
+}
+
+#[test]
+fn tint_is_reached_after_the_tint_distance() {
+    let material = Material::new(Color::white(), 1.0, OPAQUE, 1.5).with_tint(Color::new(0.5, 1.0, 0.25), 2.0);
+    let after = material.transmittance(2.0);
+    assert!((after.r - 0.5).abs() < EPSILON && (after.g - 1.0).abs() < EPSILON && (after.b - 0.25).abs() < EPSILON);
+    assert!((material.transmittance(4.0).r - 0.25).abs() < EPSILON);
+}
+
+#[test]
+fn tinted_volumes_absorb_once_along_the_path() {
+    // Agua sin desvío (ior 1) que conserva la mitad del rojo por unidad: tres unidades de
+    // recorrido dejan 0.5³, sea un solo cubo o una columna de tres bloques iguales
+    let water = Material::new(Color::white(), 1.0, [0.0, 0.0, 0.0, 1.0], 1.0).with_tint(Color::new(0.5, 1.0, 1.0), 1.0);
+    let mut cube = Scene::new();
+    cube.add(Cube::new(Vec3::zeros(), Vec3::new(1.0, 3.0, 1.0), water.clone()), ObjectProperties::default());
+    let mut grid = VoxelGrid::new(1.0);
+    let block = grid.register_block(water, ObjectProperties::default());
+    grid.fill([0, 0, 0], [1, 3, 1], block);
+    let mut column = Scene::new();
+    column.add(grid, ObjectProperties::default());
+
+    for mut scene in [cube, column] {
+        scene.sky = Sky::uniform(Color::white());
+        let mut rng = Rng::new(1, 1);
+        let color = cast_ray(&Vec3::new(0.5, 5.0, 0.5), &-Vec3::y(), &scene, &[], 0, RayCone::none(), &mut rng);
+        assert!((color.r - 0.125).abs() < 1e-3 && (color.g - 1.0).abs() < 1e-3, "{}", color);
+    }
+}
+
+#[test]
+fn scene_exit_distance_measures_the_containing_volume() {
+    let mut scene = Scene::new();
+    scene.add(slab(0.0, OPAQUE), ObjectProperties::default());
+    scene.add(Sphere::new(Vec3::new(5.0, 0.0, 0.0), 1.0, Material::black()), ObjectProperties::default());
+
+    for _ in 0..2 {
+        let inside_cube = scene.exit_distance(&Vec3::new(0.0, 0.25, 0.0), &Vec3::y()).unwrap();
+        assert!((inside_cube - 0.75).abs() < EPSILON);
+        let inside_sphere = scene.exit_distance(&Vec3::new(5.0, 0.0, 0.0), &-Vec3::x()).unwrap();
+        assert!((inside_sphere - 1.0).abs() < EPSILON);
+        assert_eq!(scene.exit_distance(&Vec3::new(3.0, 0.0, 0.0), &Vec3::x()), None);
+        scene.build_bvh();
+    }
+}
@@ -0,0 +1,127 @@
+// Luces direccionales, ciclo de día y las directivas `light direction=` y `sun`
+use std::path::Path;
+
+use nalgebra_glm::Vec3;
+
+use computer_graphics_v3::castray::cast_shadow;
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::light::{Light, LightKind};
+use computer_graphics_v3::sampling::Rng;
+use computer_graphics_v3::scene::{Scene, ObjectProperties};
+use computer_graphics_v3::scene_loader::parse_scene;
+use computer_graphics_v3::sky::DayCycle;
+
+mod common;
+use common::{shaded_point, slab, OPAQUE};
+
+#[test]
+fn directional_light_is_the_same_everywhere() {
+    let sun = Light::directional(Vec3::new(1.0, 2.0, 0.0), Color::white(), 1.0, 0.0);
+    let (near, near_distance) = sun.direction_from(&Vec3::zeros());
+    let (far, far_distance) = sun.direction_from(&Vec3::new(100.0, -50.0, 3.0));
+    assert!((near - Vec3::new(1.0, 2.0, 0.0).normalize()).magnitude() < 1e-6);
+    assert_eq!(near, far);
+    assert!(near_distance.is_infinite() && far_distance.is_infinite());
+}
+
+#[test]
+fn directional_shadows_reach_any_distance() {
+    // Una luz puntual bajo el bloque no lo ve; el sol, que está infinitamente lejos, sí
+    let mut scene = Scene::new();
+    scene.add(slab(1000.0, OPAQUE), ObjectProperties::default());
+    let sun = Light::directional(Vec3::y(), Color::white(), 1.0, 0.0);
+    let lamp = Light::new(Vec3::new(0.0, 10.0, 0.0), Color::white(), 1.0);
+    assert_eq!(cast_shadow(&shaded_point(Vec3::new(0.0, 0.0, 0.0)), &sun, &scene), 1.0);
+    assert_eq!(cast_shadow(&shaded_point(Vec3::new(0.0, 0.0, 0.0)), &lamp, &scene), 0.0);
+
+    // Los rayos son paralelos: un punto fuera de la columna del bloque queda iluminado
+    assert_eq!(cast_shadow(&shaded_point(Vec3::new(3.0, 0.0, 0.0)), &sun, &scene), 0.0);
+}
+
+#[test]
+fn sun_samples_stay_inside_its_disc() {
+    let angular_diameter = 0.2;
+    let sun = Light::directional(Vec3::new(0.0, 1.0, 1.0), Color::white(), 1.0, angular_diameter);
+    let (center, _) = sun.direction_from(&Vec3::zeros());
+    let mut rng = Rng::new(3, 4);
+    let mut widest: f32 = 0.0;
+    for _ in 0..500 {
+        let (direction, distance) = sun.sample_direction(&Vec3::zeros(), &mut rng);
+        assert!(distance.is_infinite());
+        widest = widest.max(direction.dot(&center).clamp(-1.0, 1.0).acos());
+    }
+    assert!(widest <= angular_diameter / 2.0 + 1e-3, "{}", widest);
+    assert!(widest > angular_diameter / 4.0, "{}", widest);
+}
+
+#[test]
+fn day_cycle_follows_the_clock() {
+    let noon = DayCycle::new(12.0);
+    let midnight = DayCycle::new(0.0);
+    assert!(noon.sun_elevation() > 0.9, "{}", noon.sun_elevation());
+    assert!(midnight.sun_elevation() < -0.9, "{}", midnight.sun_elevation());
+
+    // Sale por +x y se pone por -x
+    assert!(DayCycle::new(6.0).sun_direction().x > 0.99);
+    assert!(DayCycle::new(18.0).sun_direction().x < -0.99);
+
+    // De noche solo queda la luna, y el cielo es más oscuro
+    assert!(noon.sun().intensity > 0.9 && noon.moon().intensity == 0.0);
+    assert!(midnight.sun().intensity == 0.0 && midnight.moon().intensity > 0.0);
+    let up = Vec3::y();
+    assert!(midnight.sky().color(&up).luminance() < 0.1 * noon.sky().color(&up).luminance());
+
+    let mut clock = DayCycle::new(23.0);
+    clock.advance(2.0);
+    assert!((clock.time - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn scene_lights_take_position_or_direction() {
+    let source = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+light position=0,4,0
+light direction=0,2,0 intensity=0.5 angle=1
+";
+    let loaded = parse_scene(source, Path::new(".")).unwrap();
+    assert!(matches!(loaded.lights[0].kind, LightKind::Point { .. }));
+    match &loaded.lights[1].kind {
+        LightKind::Directional { direction, angular_diameter } => {
+            assert_eq!(*direction, Vec3::y());
+            assert!((angular_diameter - 1f32.to_radians()).abs() < 1e-6);
+        }
+        kind => panic!("se esperaba una luz direccional: {:?}", kind),
+    }
+
+    for bad in ["light position=0,4,0 direction=0,1,0", "light color=255,0,0", "light position=0,4,0 angle=1"] {
+        let error = parse_scene(&source.replace("light position=0,4,0", bad), Path::new(".")).err();
+        assert!(error.is_some(), "`{}` debería fallar", bad);
+    }
+}
+
+#[test]
+fn sun_directive_moves_sun_moon_and_sky() {
+    let source = "
+camera eye=0,0,5 center=0,0,0 up=0,1,0
+light position=0,4,0
+sun time=12 intensity=2
+";
+    let mut loaded = parse_scene(source, Path::new(".")).unwrap();
+    assert_eq!(loaded.lights.len(), 3);
+    assert_eq!(loaded.day_cycle().map(|day| day.time), Some(12.0));
+    assert!((loaded.lights[1].intensity - 2.0).abs() < 1e-5);
+    let day_sky = loaded.scene.sky;
+
+    // Doce horas después es medianoche: el sol se apaga, la luna sale y el cielo cambia
+    assert!(loaded.advance_day(12.0));
+    assert_eq!(loaded.lights[1].intensity, 0.0);
+    assert!(loaded.lights[2].intensity > 0.0);
+    assert_ne!(loaded.scene.sky, day_sky);
+    assert!(matches!(loaded.lights[0].kind, LightKind::Point { .. }));
+
+    let twice = format!("{}sun time=3\n", source);
+    assert!(parse_scene(&twice, Path::new(".")).is_err());
+
+    let mut no_sun = parse_scene("camera eye=0,0,5 center=0,0,0 up=0,1,0\n", Path::new(".")).unwrap();
+    assert!(!no_sun.advance_day(1.0));
+}
@@ -0,0 +1,132 @@
+// Muestreo de texturas: filtros y modos de ajuste de bordes
+use computer_graphics_v3::atlas::Tile;
+use computer_graphics_v3::color::Color;
+use computer_graphics_v3::texture::{Texture, TextureFilter, WrapMode};
+
+mod common;
+use common::temp_image;
+
+const EPSILON: f32 = 1e-4;
+
+// Textura lineal de 2x2: fila superior negro, blanco; inferior rojo, verde
+fn checker(filter: TextureFilter, wrap: WrapMode) -> Texture {
+    let path = temp_image("checker.png", || {
+        let pixels = [[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 255, 0]];
+        image::RgbImage::from_fn(2, 2, |x, y| image::Rgb(pixels[(y * 2 + x) as usize])).into()
+    });
+    Texture::new_linear(&path.to_string_lossy()).with_sampling(filter, wrap)
+}
+
+fn assert_color_eq(actual: Color, expected: Color) {
+    let difference = (actual.r - expected.r).abs() + (actual.g - expected.g).abs() + (actual.b - expected.b).abs();
+    assert!(difference < EPSILON, "se esperaba {}, se obtuvo {}", expected, actual);
+}
+
+const BLACK: Color = Color::new(0.0, 0.0, 0.0);
+const WHITE: Color = Color::new(1.0, 1.0, 1.0);
+const RED: Color = Color::new(1.0, 0.0, 0.0);
+const GREEN: Color = Color::new(0.0, 1.0, 0.0);
+
+#[test]
+fn nearest_picks_the_containing_texel() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Repeat);
+    assert_color_eq(texture.sample(0.25, 0.75), BLACK);
+    assert_color_eq(texture.sample(0.75, 0.75), WHITE);
+    assert_color_eq(texture.sample(0.25, 0.25), RED);
+    assert_color_eq(texture.sample(0.75, 0.25), GREEN);
+    // Los bordes exactos siguen dentro de la imagen, sin magenta
+    assert_color_eq(texture.sample(0.0, 1.0), BLACK);
+}
+
+#[test]
+fn repeat_wraps_around() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Repeat);
+    assert_color_eq(texture.sample(1.25, 0.75), BLACK);
+    assert_color_eq(texture.sample(-0.25, 0.75), WHITE);
+    assert_color_eq(texture.sample(0.25, -0.75), RED);
+}
+
+#[test]
+fn clamp_extends_the_edge() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Clamp);
+    assert_color_eq(texture.sample(3.0, 0.75), WHITE);
+    assert_color_eq(texture.sample(-3.0, 0.75), BLACK);
+    assert_color_eq(texture.sample(0.25, -2.0), RED);
+}
+
+#[test]
+fn mirror_flips_every_other_tile() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Mirror);
+    assert_color_eq(texture.sample(1.25, 0.75), WHITE);
+    assert_color_eq(texture.sample(1.75, 0.75), BLACK);
+    assert_color_eq(texture.sample(2.25, 0.75), BLACK);
+    assert_color_eq(texture.sample(-0.25, 0.75), BLACK);
+}
+
+#[test]
+fn bilinear_is_exact_at_texel_centers() {
+    let texture = checker(TextureFilter::Bilinear, WrapMode::Clamp);
+    assert_color_eq(texture.sample(0.25, 0.75), BLACK);
+    assert_color_eq(texture.sample(0.75, 0.25), GREEN);
+}
+
+#[test]
+fn bilinear_blends_between_texels() {
+    let texture = checker(TextureFilter::Bilinear, WrapMode::Clamp);
+    assert_color_eq(texture.sample(0.5, 0.75), Color::new(0.5, 0.5, 0.5));
+    assert_color_eq(texture.sample(0.5, 0.5), Color::new(0.5, 0.5, 0.25));
+}
+
+#[test]
+fn bilinear_repeat_blends_across_the_seam() {
+    let texture = checker(TextureFilter::Bilinear, WrapMode::Repeat);
+    assert_color_eq(texture.sample(0.0, 0.75), Color::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn tiles_wrap_inside_their_rectangle() {
+    // Mosaico de una sola columna: nunca debe tomar color de la columna vecina
+    let texture = checker(TextureFilter::Bilinear, WrapMode::Repeat);
+    let column = Tile::new(1, 0, 1, 2);
+    for u in [0.0, 0.5, 1.0, 1.5] {
+        assert_color_eq(texture.sample_tile(u, 0.75, &column), WHITE);
+    }
+}
+
+#[test]
+fn mip_chain_goes_down_to_one_texel() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Repeat);
+    assert_eq!(texture.mip_levels(), 2);
+}
+
+#[test]
+fn large_footprints_use_the_averaged_level() {
+    let texture = checker(TextureFilter::Nearest, WrapMode::Repeat);
+    let full = Tile::full(&texture);
+    let average = Color::new(0.5, 0.5, 0.25);
+
+    // Huella menor a un texel: resolución completa
+    assert_color_eq(texture.sample_filtered(0.25, 0.75, &full, 0.0), BLACK);
+    assert_color_eq(texture.sample_filtered(0.25, 0.75, &full, 0.2), BLACK);
+    // La huella cubre toda la textura: el nivel 1x1
+    assert_color_eq(texture.sample_filtered(0.25, 0.75, &full, 1.0), average);
+    assert_color_eq(texture.sample_filtered(0.75, 0.25, &full, 10.0), average);
+}
+
+#[test]
+fn trilinear_blends_between_levels() {
+    let texture = checker(TextureFilter::Bilinear, WrapMode::Clamp);
+    let full = Tile::full(&texture);
+    // lod = log2(0.75 * 2) ≈ 0.585: entre el negro del nivel 0 y el promedio del nivel 1
+    let color = texture.sample_filtered(0.25, 0.75, &full, 0.75);
+    let t = 1.5f32.log2();
+    assert_color_eq(color, Color::new(0.5 * t, 0.5 * t, 0.25 * t));
+}
+
+#[test]
+fn atlas_tiles_stop_at_the_last_aligned_level() {
+    // Una columna de 1 texel no puede reducirse sin mezclarse con la vecina
+    let texture = checker(TextureFilter::Nearest, WrapMode::Repeat);
+    let column = Tile::new(1, 0, 1, 2);
+    assert_color_eq(texture.sample_filtered(0.5, 0.75, &column, 100.0), WHITE);
+}